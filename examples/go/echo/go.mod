@@ -0,0 +1,3 @@
+module ram/examples/go/echo
+
+go 1.13
@@ -1,8 +1,8 @@
 package main
 
 import (
-	"./api"
-	_ "./model"
+	"ram/examples/go/echo/api"
+	_ "ram/examples/go/echo/model"
 	"github.com/labstack/echo"
 )
 
@@ -1,6 +1,9 @@
-use failure::{format_err, Error};
+use super::error::{self, Error};
+use super::lang::Ejected;
+use super::manifest;
+use super::VERSION;
 use rust_embed::RustEmbed;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(RustEmbed)]
 #[folder = "assets/"]
@@ -9,13 +12,103 @@ pub struct Assets;
 impl Assets {
     /// Tries to read file first from fs
     /// then from bundled assets
-    pub fn read_file(path: &Path) -> Result<String, Error> {
+    pub fn read_file(path: &Path) -> error::Result<String> {
         std::fs::read_to_string(path).or_else(|_| {
-            let pathstr = path.to_str().unwrap();
-            Self::get(pathstr)
-                .and_then(|cow| Some(cow.into_owned()))
-                .and_then(|bytes| String::from_utf8(bytes).ok())
-                .ok_or(format_err!("failed to read asset: {}", pathstr))
+            path.to_str()
+                .and_then(Self::get)
+                .and_then(|cow| String::from_utf8(cow.into_owned()).ok())
+                .ok_or_else(|| Error::file(path, "failed to read file or asset"))
         })
     }
+
+    /// Returns names of built-in langs, which are asset dirs with a `<lang>/<lang>.yaml` spec
+    pub fn langs() -> Vec<String> {
+        let mut langs: Vec<String> = Self::iter()
+            .filter_map(|file| {
+                let path = Path::new(file.as_ref());
+                let dir = path.parent()?.to_str()?;
+                let is_spec =
+                    path.file_stem()?.to_str()? == dir && path.extension()?.to_str()? == "yaml";
+                match is_spec {
+                    true => Some(dir.to_string()),
+                    _ => None,
+                }
+            })
+            .collect();
+        langs.sort();
+        langs
+    }
+
+    /// Writes spec and templates of a built-in lang into dir.
+    /// Existing files are overwritten only if `force` is set.
+    /// Spec records hashes of the built-in files, which are compared to current ones when it's loaded.
+    /// Returns path of ejected spec.
+    pub fn eject(lang: &str, dir: &Path, force: bool) -> error::Result<PathBuf> {
+        if !Self::langs().iter().any(|l| l == lang) {
+            return Err(Error::config(
+                "lang",
+                format!("'{}' is not a built-in lang", lang),
+            ));
+        }
+
+        // read from bundled assets only, local files could shadow them
+        let prefix = format!("{}/", lang);
+        let files: Vec<(String, Vec<u8>)> = Self::iter()
+            .filter_map(|file| {
+                let name = file.strip_prefix(&prefix)?.to_string();
+                Some((name, Self::get(&file)?.into_owned()))
+            })
+            .collect();
+
+        if !force {
+            if let Some(path) = files
+                .iter()
+                .map(|(name, _)| dir.join(name))
+                .find(|p| p.exists())
+            {
+                return Err(Error::file(path, "file exists, use --force to overwrite"));
+            }
+        }
+
+        let spec_name = format!("{}.yaml", lang);
+        let hashes: String = files
+            .iter()
+            .map(|(name, data)| format!("    {:?}: \"{}\"\n", name, manifest::hash(data)))
+            .collect();
+        let header = format!(
+            "# Ejected from built-in {lang} lang\nejected_from:\n  lang: {lang}\n  version: \"{version}\"\n  hashes:\n{hashes}\n",
+            lang = lang,
+            version = VERSION,
+            hashes = hashes
+        );
+
+        for (name, data) in files {
+            let data = match name == spec_name {
+                true => [header.as_bytes(), data.as_slice()].concat(),
+                _ => data,
+            };
+            let path = dir.join(&name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| Error::file(parent, e))?;
+            }
+            std::fs::write(&path, data).map_err(|e| Error::file(&path, e))?;
+        }
+        Ok(dir.join(spec_name))
+    }
+
+    /// Returns files of ejected lang that differ from current built-in files, or are not built-in anymore
+    pub fn changed_since_eject(ejected: &Ejected) -> Vec<String> {
+        let mut changed: Vec<String> = ejected
+            .hashes
+            .iter()
+            .filter(|(name, hash)| {
+                Self::get(&format!("{}/{}", ejected.lang, name))
+                    .map(|data| manifest::hash(&data) != **hash)
+                    .unwrap_or(true)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        changed.sort();
+        changed
+    }
 }
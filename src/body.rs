@@ -0,0 +1,74 @@
+use super::resource::operations;
+use super::util;
+use super::Model;
+use indexmap::IndexMap;
+use inflector::Inflector;
+use openapi::v3_0::{ObjectOrReference, Operation, PathItem, RequestBody};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Body {
+    pub model: Model,
+    pub required: bool,
+    pub content_type: String,
+    pub description: Option<String>,
+}
+
+/// Returns request body of operation, resolving references to `components/requestBodies`
+pub fn get_body_operation(
+    operation: &Operation,
+    request_bodies: &HashMap<String, RequestBody>,
+) -> Option<Body> {
+    let request_body = match operation.request_body.as_ref()? {
+        ObjectOrReference::Object(t) => t,
+        ObjectOrReference::Ref { ref_path } => {
+            util::model_name_from_ref(&ref_path).and_then(|name| request_bodies.get(&name))?
+        }
+    };
+
+    util::preferred_content(request_body.content.iter()).map(|(content_type, mediatype)| {
+        let schema = mediatype
+            .schema
+            .as_ref()
+            .map(util::obj_or_ref_schema)
+            .unwrap_or_default();
+        let required = request_body.required.unwrap_or(false);
+        let mut model = Model::new(&body_model_name(operation), &schema, "");
+        model.required = required;
+        Body {
+            model,
+            required,
+            content_type: content_type.clone(),
+            description: request_body.description.clone(),
+        }
+    })
+}
+
+/// Returns models of inline object request bodies, named after their operations.
+/// Rendered with other models, so that body types of resources are defined.
+pub fn inline_body_models(
+    paths: &IndexMap<String, PathItem>,
+    request_bodies: &HashMap<String, RequestBody>,
+) -> Vec<Model> {
+    paths
+        .values()
+        .flat_map(operations)
+        .filter_map(|(_, op)| get_body_operation(op, request_bodies))
+        .map(|body| body.model)
+        .filter(|m| m.is_object && m.ref_path.is_none())
+        .map(|mut m| {
+            m.def = m.name.clone();
+            m.required = false;
+            m
+        })
+        .collect()
+}
+
+// inline body model is named by operation, e.g. `createPet` -> `CreatePetBody`
+fn body_model_name(operation: &Operation) -> String {
+    match operation.operation_id {
+        Some(ref id) => format!("{}Body", id.to_pascal_case()),
+        None => "Body".into(),
+    }
+}
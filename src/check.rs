@@ -0,0 +1,285 @@
+use super::error::{Error, Result};
+use super::ignore::Ignore;
+use super::regions;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// lines of context around changes in diffs
+const CONTEXT: usize = 3;
+
+/// Difference between generated file and file on disk
+#[derive(Debug, PartialEq)]
+pub enum FileCheck {
+    /// Generated file does not exist on disk
+    Missing(PathBuf),
+    /// File on disk differs from generated, with unified diff from disk to generated
+    Changed(PathBuf, String),
+    /// File on disk is not generated anymore.
+    /// Only files next to generated files with the same extension are considered
+    Extra(PathBuf),
+}
+
+/// Compares generated files against files under root, ignored files are skipped
+pub fn check_files(
+    root: &Path,
+    files: &HashMap<PathBuf, String>,
+    ignore: &Ignore,
+) -> Result<Vec<FileCheck>> {
+    let is_ignored = |path: &Path| ignore.is_ignored(root, path);
+
+    let mut checks = vec![];
+    let mut paths: Vec<&PathBuf> = files.keys().collect();
+    paths.sort();
+    for file in paths.iter() {
+        let path = root.join(file);
+        if is_ignored(&path) {
+            continue;
+        }
+        if !path.exists() {
+            checks.push(FileCheck::Missing(path));
+            continue;
+        }
+        // protected regions are kept on write, so they are not differences
+        let generated = regions::splice_existing(&path, files[*file].clone())?;
+        let data = fs::read_to_string(&path).map_err(|e| Error::file(&path, e))?;
+        if data != generated {
+            let name = path.display().to_string();
+            let diff = unified_diff(&data, &generated, &name, &name);
+            checks.push(FileCheck::Changed(path, diff));
+        }
+    }
+
+    // extra files in generated directories
+    let generated: HashSet<PathBuf> = files.keys().map(|f| root.join(f)).collect();
+    let mut extensions: HashMap<PathBuf, HashSet<String>> = HashMap::new();
+    for path in generated.iter() {
+        if let (Some(dir), Some(ext)) = (path.parent(), path.extension()) {
+            extensions
+                .entry(dir.to_path_buf())
+                .or_default()
+                .insert(ext.to_string_lossy().into());
+        }
+    }
+    let mut extra = vec![];
+    for (dir, exts) in extensions.iter() {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            let same_ext = path
+                .extension()
+                .map(|ext| exts.contains(ext.to_string_lossy().as_ref()))
+                .unwrap_or(false);
+            if path.is_file() && same_ext && !generated.contains(&path) && !is_ignored(&path) {
+                extra.push(path);
+            }
+        }
+    }
+    extra.sort();
+    checks.extend(extra.into_iter().map(FileCheck::Extra));
+
+    Ok(checks)
+}
+
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Returns unified diff of two texts, empty if texts are equal
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines = split_lines(old);
+    let new_lines = split_lines(new);
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    // (old, new) line index at each op
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut o, mut n) = (0, 0);
+    for op in ops.iter() {
+        positions.push((o, n));
+        match op {
+            Line::Same(_) => {
+                o += 1;
+                n += 1;
+            }
+            Line::Removed(_) => o += 1,
+            Line::Added(_) => n += 1,
+        }
+    }
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Line::Same(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let mut i = 0;
+    while i < changes.len() {
+        // changes with overlapping context belong to the same hunk
+        let mut j = i;
+        while j + 1 < changes.len() && changes[j + 1] - changes[j] <= 2 * CONTEXT + 1 {
+            j += 1;
+        }
+        let start = changes[i].saturating_sub(CONTEXT);
+        let end = (changes[j] + CONTEXT + 1).min(ops.len());
+        let hunk = &ops[start..end];
+
+        let old_count = hunk
+            .iter()
+            .filter(|op| !matches!(op, Line::Added(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|op| !matches!(op, Line::Removed(_)))
+            .count();
+        let (o, n) = positions[start];
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(o, old_count),
+            hunk_range(n, new_count)
+        ));
+        for op in hunk {
+            let (sign, line) = match op {
+                Line::Same(l) => (' ', l),
+                Line::Removed(l) => ('-', l),
+                Line::Added(l) => ('+', l),
+            };
+            out.push(sign);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        i = j + 1;
+    }
+    out
+}
+
+// splits text to lines with their line endings,
+// so that last lines with and without newline differ
+fn split_lines(text: &str) -> Vec<&str> {
+    let mut lines = vec![];
+    let mut rest = text;
+    while let Some(idx) = rest.find('\n') {
+        lines.push(&rest[..=idx]);
+        rest = &rest[idx + 1..];
+    }
+    if !rest.is_empty() {
+        lines.push(rest);
+    }
+    lines
+}
+
+// hunk range is 1-based, empty range refers to the line before
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, count),
+    }
+}
+
+// line diff by longest common subsequence, common prefix and suffix are left out of it
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Line<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut ops: Vec<Line> = old[..prefix].iter().copied().map(Line::Same).collect();
+    hirschberg(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+        &mut ops,
+    );
+    ops.extend(old[old.len() - suffix..].iter().copied().map(Line::Same));
+    ops
+}
+
+// pushes ops of longest common subsequence of a and b, in linear space:
+// a is halved and b is split where the lcs lengths of both halves add up to the longest
+fn hirschberg<'a>(a: &[&'a str], b: &[&'a str], ops: &mut Vec<Line<'a>>) {
+    match a.len() {
+        0 => ops.extend(b.iter().copied().map(Line::Added)),
+        _ if b.is_empty() => ops.extend(a.iter().copied().map(Line::Removed)),
+        1 => match b.iter().position(|line| *line == a[0]) {
+            Some(k) => {
+                ops.extend(b[..k].iter().copied().map(Line::Added));
+                ops.push(Line::Same(a[0]));
+                ops.extend(b[k + 1..].iter().copied().map(Line::Added));
+            }
+            None => {
+                ops.push(Line::Removed(a[0]));
+                ops.extend(b.iter().copied().map(Line::Added));
+            }
+        },
+        _ => {
+            let mid = a.len() / 2;
+            let head = lcs_lengths(&a[..mid], b, false);
+            let tail = lcs_lengths(&a[mid..], b, true);
+            let k = (0..=b.len())
+                .max_by_key(|&k| (head[k] + tail[b.len() - k], std::cmp::Reverse(k)))
+                .unwrap_or(0);
+            hirschberg(&a[..mid], &b[..k], ops);
+            hirschberg(&a[mid..], &b[k..], ops);
+        }
+    }
+}
+
+// lcs lengths of a and each prefix of b, or of each suffix of b when reversed,
+// keeping a single row of the table
+fn lcs_lengths(a: &[&str], b: &[&str], reversed: bool) -> Vec<usize> {
+    let mut row = vec![0usize; b.len() + 1];
+    for i in 0..a.len() {
+        // value of previous row at j, before it is overwritten
+        let mut diagonal = 0;
+        for j in 0..b.len() {
+            let above = row[j + 1];
+            let same = if reversed {
+                a[a.len() - 1 - i] == b[b.len() - 1 - j]
+            } else {
+                a[i] == b[j]
+            };
+            row[j + 1] = if same {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        assert_eq!(
+            unified_diff(old, new, "old", "new"),
+            "--- old\n+++ new\n\
+             @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -8,3 +8,4 @@\n h\n i\n j\n+k\n"
+        );
+        assert_eq!(unified_diff(old, old, "old", "new"), "");
+
+        // missing newline at end of file is a difference
+        assert_eq!(
+            unified_diff("a\nb", "a\nb\n", "old", "new"),
+            "--- old\n+++ new\n\
+             @@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n"
+        );
+    }
+}
@@ -1,13 +1,14 @@
+use super::error::{Error, Result};
 use super::util;
-use super::{AddFile, GroupingStrategy, Lang};
-use failure::Fallible;
+use super::{AddFile, ConflictPolicy, GroupingStrategy, Lang};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Config {
     #[serde(skip)]
     pub path: PathBuf,
@@ -28,59 +29,101 @@ pub struct Config {
 
     #[serde(default)]
     pub grouping_strategy: Option<GroupingStrategy>,
+
+    /// Free variables for templates, used as `{{cfg.vars.name}}`
+    #[serde(default)]
+    pub vars: HashMap<String, Value>,
+
+    /// Field number lockfile, used with langs that number fields
+    #[serde(default)]
+    pub lockfile: Option<String>,
+
+    /// Input specs, merged into one state. Used when no input is given on command line
+    #[serde(default)]
+    pub specs: Vec<String>,
+
+    /// Handling of same named models that differ between specs, defaults to error
+    #[serde(default)]
+    pub conflicts: Option<ConflictPolicy>,
 }
 
 impl Config {
-    pub fn load_file(path: &Path) -> Fallible<Config> {
-        let file = File::open(path)?;
+    pub fn load_file(path: &Path) -> Result<Config> {
+        let file = File::open(path).map_err(|e| Error::file(path, e))?;
         let reader = BufReader::new(file);
 
-        let ext = path.extension().expect("failed to get extension");
-        let ext: &str = ext.to_str().expect("failed to read extension");
+        let ext = path
+            .extension()
+            .and_then(std::ffi::OsStr::to_str)
+            .unwrap_or("json");
 
         let mut cfg: Config = match ext {
-            "yaml" | "yml" => serde_yaml::from_reader(reader)?,
-            "json" | _ => serde_json::from_reader(reader)?,
+            "yaml" | "yml" => serde_yaml::from_reader(reader).map_err(|e| Error::file(path, e))?,
+            "json" | _ => serde_json::from_reader(reader).map_err(|e| Error::file(path, e))?,
         };
 
         // set cfg path
-        cfg.path = path.canonicalize().unwrap().parent().unwrap().into();
+        cfg.path = path
+            .canonicalize()
+            .map_err(|e| Error::file(path, e))?
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
 
         Ok(cfg)
     }
 
-    pub fn get_lang(&self) -> Fallible<Lang> {
-        let f = &self.lang;
-
+    // Returns lang spec file path, built-in langs have none
+    pub fn get_lang_file(&self) -> Option<PathBuf> {
         // if file has extension set, assume its a path to file and join path
-        let mut path = PathBuf::from(f);
-        if path.extension().is_some() {
-            path = util::join_relative(&self.path, &path);
-        }
+        let path = PathBuf::from(&self.lang);
+        path.extension()
+            .map(|_| util::join_relative(&self.path, &path))
+    }
+
+    pub fn get_lang(&self) -> Result<Lang> {
+        let path = self
+            .get_lang_file()
+            .unwrap_or_else(|| PathBuf::from(&self.lang));
         // load lang file
-        Lang::load_file(&path).and_then(|mut lang| {
-            // add custom formatters to lang formatters
-            lang.helpers.extend(self.helpers.clone());
-            Ok(lang)
-        })
+        Lang::load_file(&path)
+            .map(|mut lang| {
+                // add custom formatters to lang formatters
+                lang.helpers.extend(self.helpers.clone());
+                lang
+            })
+            .map_err(|e| Error::config("lang", e))
     }
 
     // Returns formatted path according to config / lang spec defaults
-    pub fn get_path(&self, path_key: &str, lang: &Lang) -> PathBuf {
-        self.paths
-            .get(path_key)
-            .and_then(|p| Some(PathBuf::from(&p)))
-            .or_else(|| Some(lang.default_path(path_key)))
-            .unwrap()
+    pub fn get_path(&self, path_key: &str, lang: &Lang) -> Result<PathBuf> {
+        match self.paths.get(path_key) {
+            Some(p) => Ok(PathBuf::from(&p)),
+            None => lang.default_path(path_key),
+        }
+    }
+
+    // Returns lockfile path joined with config path
+    pub fn get_lockfile(&self) -> Option<PathBuf> {
+        self.lockfile
+            .as_ref()
+            .map(|f| util::join_relative(&self.path, &PathBuf::from(f)))
+    }
+
+    // Returns input spec paths, joined with cfg path
+    pub fn get_specs(&self) -> Vec<PathBuf> {
+        self.specs
+            .iter()
+            .map(|f| util::join_relative(&self.path, &PathBuf::from(f)))
+            .collect()
     }
 
     pub fn get_files(&self, lang: Option<&Lang>) -> Vec<AddFile> {
         let config_files = self.files.iter().map(|f: &AddFile| {
             // join relative cfg path
             let template = util::join_relative(&self.path, &PathBuf::from(&f.template))
-                .to_str()
-                .unwrap()
-                .to_owned();
+                .to_string_lossy()
+                .into_owned();
             AddFile {
                 template,
                 ..f.clone()
@@ -0,0 +1,128 @@
+use super::emitter::Emitter;
+use super::error::Result;
+use super::{Model, State};
+use inflector::Inflector;
+use std::path::PathBuf;
+
+const DEFAULT_FILENAME: &str = "index.d.ts";
+
+/// Emits TypeScript declarations of all models into a single `.d.ts` file
+pub struct DtsEmitter;
+
+impl Emitter for DtsEmitter {
+    fn emit(&self, state: &State, filename: Option<&str>) -> Result<Vec<(PathBuf, String)>> {
+        let data = state
+            .models
+            .iter()
+            .map(declaration)
+            .collect::<Vec<String>>()
+            .join("\n");
+        Ok(vec![(
+            PathBuf::from(filename.unwrap_or(DEFAULT_FILENAME)),
+            data,
+        )])
+    }
+}
+
+fn declaration(model: &Model) -> String {
+    let name = model.def.to_pascal_case();
+    let doc = model
+        .description
+        .as_ref()
+        .map(|d| format!("/** {} */\n", d))
+        .unwrap_or_default();
+
+    if model.is_object {
+        let mut fields: Vec<String> = model.properties.iter().map(|p| field(p)).collect();
+        if let Some(additional) = model.additional_properties.as_ref() {
+            fields.extend(additional.properties.iter().map(|p| field(p)));
+        }
+        format!(
+            "{}export interface {} {{\n{}}}\n",
+            doc,
+            name,
+            fields.concat()
+        )
+    } else {
+        format!("{}export type {} = {};\n", doc, name, ts_type(model, false))
+    }
+}
+
+fn field(model: &Model) -> String {
+    format!(
+        "  {}{}{}: {};\n",
+        if model.readonly { "readonly " } else { "" },
+        model.name,
+        if model.required { "" } else { "?" },
+        ts_type(model, true)
+    )
+}
+
+// nested models with def set are references to top-level models
+fn ts_type(model: &Model, nested: bool) -> String {
+    let t = if nested && !model.def.is_empty() {
+        model.def.to_pascal_case()
+    } else if model.is_enum {
+        model
+            .enum_values
+            .iter()
+            .map(|v| v.value.to_string())
+            .collect::<Vec<String>>()
+            .join(" | ")
+    } else if model.is_polymorphic {
+        model
+            .variants
+            .iter()
+            .map(|v| ts_type(v, true))
+            .collect::<Vec<String>>()
+            .join(" | ")
+    } else if model.is_array {
+        let item = model
+            .items
+            .as_ref()
+            .map(|i| ts_type(i, true))
+            .unwrap_or_else(|| "unknown".into());
+        format!("Array<{}>", item)
+    } else if model.is_object {
+        let fields: String = model.properties.iter().map(|p| field(p)).collect();
+        format!("{{\n{}}}", fields)
+    } else {
+        match model.openapi_type.as_str() {
+            "integer" | "number" => "number",
+            "string" => "string",
+            "boolean" => "boolean",
+            _ => "unknown",
+        }
+        .into()
+    };
+
+    match model.nullable {
+        true => format!("{} | null", t),
+        _ => t,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ts_type() {
+        let item = Model {
+            def: "pet".into(),
+            nullable: true,
+            ..Default::default()
+        };
+        let model = Model {
+            def: "pets".into(),
+            is_array: true,
+            items: Some(Box::new(item)),
+            ..Default::default()
+        };
+        assert_eq!(ts_type(&model, false), "Array<Pet | null>");
+        assert_eq!(
+            declaration(&model),
+            "export type Pets = Array<Pet | null>;\n"
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use super::dts::DtsEmitter;
+use super::error::Result;
+use super::jsonschema::JsonSchemaEmitter;
+use super::State;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Produces files from state without templates.
+/// Emitters are referred by name in lang or config `files`, with `emitter: <name>`.
+/// Besides built-in emitters, named emitters can be passed to `generate_files_with_emitters`
+pub trait Emitter {
+    /// Returns file contents by path relative to the output directory.
+    /// `filename` is the filename given in `files`, if any
+    fn emit(&self, state: &State, filename: Option<&str>) -> Result<Vec<(PathBuf, String)>>;
+}
+
+impl<T: Emitter + ?Sized> Emitter for &T {
+    fn emit(&self, state: &State, filename: Option<&str>) -> Result<Vec<(PathBuf, String)>> {
+        (**self).emit(state, filename)
+    }
+}
+
+/// Returns emitter by name, given named emitters take precedence over built-in ones
+pub fn find<'a>(name: &str, emitters: &[(&str, &'a dyn Emitter)]) -> Option<Box<dyn Emitter + 'a>> {
+    match emitters.iter().find(|(n, _)| *n == name) {
+        Some((_, emitter)) => Some(Box::new(*emitter)),
+        None => get(name),
+    }
+}
+
+/// Returns built-in emitter by name
+pub fn get(name: &str) -> Option<Box<dyn Emitter>> {
+    let mut emitters: HashMap<&str, Box<dyn Emitter>> = HashMap::new();
+    emitters.insert("jsonschema", Box::new(JsonSchemaEmitter));
+    emitters.insert("dts", Box::new(DtsEmitter));
+    emitters.remove(name)
+}
@@ -0,0 +1,94 @@
+use failure::Fail;
+use std::fmt::Display;
+
+/// Errors with the location they originate from
+#[derive(Debug, Fail)]
+pub enum Error {
+    /// Invalid or unsupported spec, `pointer` is a JSON pointer to the offending item
+    #[fail(display = "#{}: {}", pointer, message)]
+    Spec { pointer: String, message: String },
+
+    /// Invalid or missing configuration by config key
+    #[fail(display = "config '{}': {}", key, message)]
+    Config { key: String, message: String },
+
+    /// Failed to read or render template
+    #[fail(display = "template '{}': {}", template, message)]
+    Template { template: String, message: String },
+
+    /// Failed to read, parse or write file
+    #[fail(display = "{}: {}", path, message)]
+    File { path: String, message: String },
+
+    /// Failed to serialize generator state
+    #[fail(display = "serialize: {}", message)]
+    Serialize { message: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn spec(pointer: &str, message: impl Display) -> Self {
+        Error::Spec {
+            pointer: pointer.into(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn config(key: &str, message: impl Display) -> Self {
+        Error::Config {
+            key: key.into(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn template(template: &str, message: impl Display) -> Self {
+        Error::Template {
+            template: template.into(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn file(path: impl AsRef<std::path::Path>, message: impl Display) -> Self {
+        Error::File {
+            path: path.as_ref().display().to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn serialize(message: impl Display) -> Self {
+        Error::Serialize {
+            message: message.to_string(),
+        }
+    }
+
+    /// Prefixes spec error pointer with given segments, other errors are returned as is.
+    /// Segments are escaped as JSON pointer tokens.
+    pub fn within(self, segments: &[&str]) -> Self {
+        match self {
+            Error::Spec { pointer, message } => Error::Spec {
+                pointer: segments
+                    .iter()
+                    .map(|s| format!("/{}", s.replace('~', "~0").replace('/', "~1")))
+                    .collect::<String>()
+                    + &pointer,
+                message,
+            },
+            e => e,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_within() {
+        let e = Error::spec("/type", "unsupported type").within(&["paths", "/farm/{id}", "get"]);
+        assert_eq!(
+            e.to_string(),
+            "#/paths/~1farm~1{id}/get/type: unsupported type"
+        );
+    }
+}
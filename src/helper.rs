@@ -8,6 +8,13 @@ handlebars_helper!(snakecase: |s: str| s.to_snake_case());
 handlebars_helper!(screamingcase: |s: str| s.to_screaming_snake_case());
 handlebars_helper!(camelcase: |s: str| s.to_camel_case());
 handlebars_helper!(kebabcase: |s: str| s.to_kebab_case());
+handlebars_helper!(json: |v: Json| v.to_string());
+handlebars_helper!(jskey: |s: str| if is_js_identifier(s) {
+    s.to_string()
+} else {
+    serde_json::Value::from(s).to_string()
+});
+handlebars_helper!(sqlstring: |v: Json| sql_string(v));
 
 pub fn register_helpers(hb: &mut Handlebars) {
     hb.register_helper("lowercase", Box::new(lowercase));
@@ -17,4 +24,26 @@ pub fn register_helpers(hb: &mut Handlebars) {
     hb.register_helper("screamingcase", Box::new(screamingcase));
     hb.register_helper("camelcase", Box::new(camelcase));
     hb.register_helper("kebabcase", Box::new(kebabcase));
+    hb.register_helper("json", Box::new(json));
+    hb.register_helper("sqlstring", Box::new(sqlstring));
+    hb.register_helper("jskey", Box::new(jskey));
+}
+
+// quotes value as sql string literal, also used by migrations
+pub(crate) fn sql_string(v: &serde_json::Value) -> String {
+    let s = v
+        .as_str()
+        .map(String::from)
+        .unwrap_or_else(|| v.to_string());
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+// checks if str can be used as unquoted js object key
+fn is_js_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        .unwrap_or(false)
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
 }
@@ -0,0 +1,156 @@
+use super::error::{Error, Result};
+use glob::{MatchOptions, Pattern};
+use std::path::{Component, Path, PathBuf};
+
+/// Name of ignore file looked up from output root and config directory
+pub const IGNORE_FILE: &str = ".ramignore";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Ignore rules in `.gitignore` format, matched against paths relative to output root
+#[derive(Debug, Default, Clone)]
+pub struct Ignore {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: Pattern,
+    /// `!pattern`, re-includes matched paths
+    negated: bool,
+    /// `pattern/`, matches only directories
+    dir_only: bool,
+    /// pattern with a leading or middle slash matches from root,
+    /// others match file or directory name at any level
+    anchored: bool,
+}
+
+impl Ignore {
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(|l| l.trim_end())
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter_map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                Pattern::new(line.trim_start_matches('/'))
+                    .ok()
+                    .map(|pattern| Rule {
+                        pattern,
+                        negated,
+                        dir_only,
+                        anchored,
+                    })
+            })
+            .collect();
+        Ignore { rules }
+    }
+
+    pub fn load_file(path: &Path) -> Result<Self> {
+        std::fs::read_to_string(path)
+            .map(|contents| Self::parse(&contents))
+            .map_err(|e| Error::file(path, e))
+    }
+
+    /// Reads `.ramignore` from config directory and output root, if they exist.
+    /// Rules of output root come last, so they take precedence.
+    pub fn lookup(root: &Path, cfg_dir: Option<&Path>) -> Self {
+        let mut rules = vec![];
+        let mut dirs: Vec<&Path> = cfg_dir.into_iter().collect();
+        if !dirs.iter().any(|d| same_dir(d, root)) {
+            dirs.push(root);
+        }
+        for dir in dirs {
+            if let Ok(contents) = std::fs::read_to_string(dir.join(IGNORE_FILE)) {
+                rules.extend(Self::parse(&contents).rules);
+            }
+        }
+        Ignore { rules }
+    }
+
+    /// Returns true if path under root is ignored.
+    /// Path can be relative to root or joined with it.
+    pub fn is_ignored(&self, root: &Path, path: &Path) -> bool {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let components: Vec<Component> = relative
+            .components()
+            .filter(|c| *c != Component::CurDir)
+            .collect();
+
+        // files of an ignored directory can't be re-included
+        let mut current = PathBuf::new();
+        for (idx, c) in components.iter().enumerate() {
+            current.push(c);
+            if self.matches(&current, idx + 1 < components.len()) {
+                return true;
+            }
+        }
+        false
+    }
+
+    // last matching rule decides
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        let name = path.file_name().map(Path::new).unwrap_or(path);
+        self.rules
+            .iter()
+            .filter(|r| is_dir || !r.dir_only)
+            .filter(|r| {
+                r.pattern
+                    .matches_path_with(if r.anchored { path } else { name }, MATCH_OPTIONS)
+            })
+            .last()
+            .map(|r| !r.negated)
+            .unwrap_or(false)
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored() {
+        let ignore = Ignore::parse(
+            "# comment\n\
+             *.rs\n\
+             !keep.rs\n\
+             /mod.go\n\
+             build/\n\
+             docs/*.md\n",
+        );
+        let root = Path::new("out");
+        let ignored = |p: &str| ignore.is_ignored(root, Path::new(p));
+
+        assert!(ignored("src/model/pet.rs"));
+        assert!(!ignored("src/model/keep.rs"));
+        // anchored
+        assert!(ignored("mod.go"));
+        assert!(!ignored("src/mod.go"));
+        assert!(ignored("docs/index.md"));
+        assert!(!ignored("src/docs/index.md"));
+        // directory only
+        assert!(ignored("src/build/pet.go"));
+        assert!(!ignored("build"));
+        // joined with root
+        assert!(ignored("out/mod.go"));
+    }
+}
@@ -0,0 +1,110 @@
+use super::assets::Assets;
+use super::error::{Error, Result};
+use super::lang::{AddFile, Lang};
+use std::path::{Path, PathBuf};
+
+/// Directory of copied templates, relative to config
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// Returns a config and copies of templates of a built-in lang, by path relative to config directory.
+/// Files rendered from copied templates override the ones rendered from built-in templates.
+pub fn init_files(lang: &str) -> Result<Vec<(PathBuf, String)>> {
+    if !Assets::langs().iter().any(|l| l == lang) {
+        return Err(Error::config(
+            "lang",
+            format!("'{}' is not a built-in lang", lang),
+        ));
+    }
+    let spec = Lang::load_file(Path::new(lang))?;
+
+    let mut files = vec![];
+    let mut entries = vec![];
+    for f in spec.files.iter().filter(|f| f.emitter.is_none()) {
+        let source = Path::new(lang).join(&f.template);
+        let data = Assets::read_file(&source).map_err(|e| Error::template(&f.template, e))?;
+        let template = Path::new(TEMPLATES_DIR).join(&f.template);
+        entries.push(file_entry(f, &template));
+        files.push((template, data));
+    }
+
+    let config = format!(
+        "# Target lang spec\n\
+         lang: \"{lang}\"\n\
+         \n\
+         # Copies of built-in {lang} templates, generated files override the built-in ones\n\
+         files:\n{entries}",
+        lang = lang,
+        entries = entries.concat()
+    );
+    files.insert(0, (PathBuf::from("config.yaml"), config));
+    Ok(files)
+}
+
+// config yaml entry of a file, rendered from given template
+fn file_entry(f: &AddFile, template: &Path) -> String {
+    let mut entry = format!("  - template: \"{}\"\n", template.display());
+    let fields = vec![
+        ("filename", &f.filename),
+        ("in", &f.file_in),
+        ("path", &f.path),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            entry.push_str(&format!("    {}: \"{}\"\n", key, value));
+        }
+    }
+    entry
+}
+
+/// Sets `lang` of config yaml, keeping the rest of it as is.
+/// Adds `lang` at the beginning if config does not have it.
+pub fn set_config_lang(config: &str, lang: &str) -> String {
+    let line = format!("lang: \"{}\"", lang);
+    let mut found = false;
+    let mut lines: Vec<String> = config
+        .lines()
+        .map(|l| match l.starts_with("lang:") && !found {
+            true => {
+                found = true;
+                line.clone()
+            }
+            _ => l.to_string(),
+        })
+        .collect();
+    if !found {
+        lines.insert(0, line);
+    }
+    let mut config = lines.join("\n");
+    config.push('\n');
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_files() {
+        let files = init_files("rust").unwrap();
+        let (path, config) = &files[0];
+        assert_eq!(path, &PathBuf::from("config.yaml"));
+        assert!(config.contains("  - template: \"templates/model.hbs\"\n    in: \"model\"\n"));
+        assert!(files
+            .iter()
+            .any(|(path, _)| path == &PathBuf::from("templates/mod_model.hbs")));
+
+        assert!(init_files("cobol").is_err());
+    }
+
+    #[test]
+    fn test_set_config_lang() {
+        assert_eq!(
+            set_config_lang("# lang\nlang: rust\npaths:\n  lang: x\n", "rust/rust.yaml"),
+            "# lang\nlang: \"rust/rust.yaml\"\npaths:\n  lang: x\n"
+        );
+        assert_eq!(
+            set_config_lang("paths: {}", "go.yaml"),
+            "lang: \"go.yaml\"\npaths: {}\n"
+        );
+    }
+}
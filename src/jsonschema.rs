@@ -0,0 +1,207 @@
+use super::emitter::Emitter;
+use super::error::Result;
+use super::{Model, State};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Emits a standalone JSON Schema (draft 2020-12) per model,
+/// with referenced models bundled in `$defs`
+pub struct JsonSchemaEmitter;
+
+impl Emitter for JsonSchemaEmitter {
+    fn emit(&self, state: &State, _: Option<&str>) -> Result<Vec<(PathBuf, String)>> {
+        let models_map: HashMap<&str, &Model> =
+            state.models.iter().map(|m| (m.def.as_str(), m)).collect();
+
+        Ok(state
+            .models
+            .iter()
+            .map(|model| {
+                let filename = format!("{}.schema.json", model.def);
+
+                let mut root = json!({
+                    "$schema": DRAFT,
+                    "$id": filename,
+                    "title": model.def,
+                });
+                merge(&mut root, schema(model, &model.def));
+
+                let defs: Map<String, Value> = bundled_references(model, &models_map)
+                    .into_iter()
+                    .map(|m| (m.def.clone(), schema(m, &model.def)))
+                    .collect();
+                if !defs.is_empty() {
+                    root["$defs"] = Value::Object(defs);
+                }
+
+                let data = serde_json::to_string_pretty(&root).unwrap_or_default();
+                (PathBuf::from(filename), data)
+            })
+            .collect())
+    }
+}
+
+// collects models referenced by model, recursively
+fn bundled_references<'a>(model: &Model, models_map: &HashMap<&str, &'a Model>) -> Vec<&'a Model> {
+    let mut bundled: Vec<&Model> = vec![];
+    let mut queue: Vec<&str> = model.references.iter().map(String::as_str).collect();
+    while let Some(name) = queue.pop() {
+        if name == model.def || bundled.iter().any(|m| m.def == name) {
+            continue;
+        }
+        if let Some(m) = models_map.get(name) {
+            bundled.push(m);
+            queue.extend(m.references.iter().map(String::as_str));
+        }
+    }
+    bundled.sort_by(|a, b| a.def.cmp(&b.def));
+    bundled
+}
+
+fn merge(target: &mut Value, source: Value) {
+    if let (Value::Object(target), Value::Object(source)) = (target, source) {
+        target.extend(source);
+    }
+}
+
+// nested models with def set are references to top-level models,
+// references to the root model point to the document itself
+fn property_schema(model: &Model, root: &str) -> Value {
+    if model.def.is_empty() {
+        return schema(model, root);
+    }
+    let reference = match model.def == root {
+        true => json!({ "$ref": "#" }),
+        _ => json!({ "$ref": format!("#/$defs/{}", model.def) }),
+    };
+    let mut s = match model.nullable {
+        true => json!({ "anyOf": [reference, { "type": "null" }] }),
+        _ => reference,
+    };
+    annotate(&mut s, model);
+    s
+}
+
+// schema of model itself
+fn schema(model: &Model, root: &str) -> Value {
+    let mut s = if model.is_polymorphic {
+        json!({
+            "oneOf": model.variants.iter().map(|v| property_schema(v, root)).collect::<Vec<_>>()
+        })
+    } else if model.is_array {
+        json!({
+            "type": "array",
+            "items": model.items.as_ref().map(|i| property_schema(i, root)).unwrap_or(json!({})),
+        })
+    } else if model.is_object {
+        let properties: Map<String, Value> = model
+            .properties
+            .iter()
+            .map(|p| (p.name.clone(), property_schema(p, root)))
+            .collect();
+        let required: Vec<&str> = model
+            .properties
+            .iter()
+            .filter(|p| p.required)
+            .map(|p| p.name.as_str())
+            .collect();
+
+        let mut s = json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+            s["required"] = json!(required);
+        }
+        if let Some(additional) = model.additional_properties.as_ref() {
+            s["additionalProperties"] = property_schema(additional, root);
+        }
+        s
+    } else {
+        let mut s = json!({ "type": model.openapi_type });
+        if let Some(format) = model.format.as_ref() {
+            s["format"] = json!(format);
+        }
+        s
+    };
+
+    if model.is_enum {
+        s["enum"] = model.enum_values.iter().map(|v| v.value.clone()).collect();
+    }
+
+    // nullable types are expressed as type arrays
+    if model.nullable {
+        if let Some(t) = s.get("type").cloned() {
+            s["type"] = json!([t, "null"]);
+            if let Some(values) = s.get_mut("enum").and_then(Value::as_array_mut) {
+                values.push(Value::Null);
+            }
+        }
+    }
+
+    if let Some(c) = model.constraints.as_ref() {
+        let bound = |exclusive: bool, inclusive: &str, exclusive_key: &str| match exclusive {
+            true => exclusive_key.to_string(),
+            _ => inclusive.to_string(),
+        };
+        let keywords = vec![
+            (
+                bound(c.exclusive_minimum, "minimum", "exclusiveMinimum"),
+                c.minimum.clone(),
+            ),
+            (
+                bound(c.exclusive_maximum, "maximum", "exclusiveMaximum"),
+                c.maximum.clone(),
+            ),
+            ("multipleOf".into(), c.multiple_of.clone()),
+            ("minLength".into(), c.min_length.clone()),
+            ("maxLength".into(), c.max_length.clone()),
+            ("pattern".into(), c.pattern.clone().map(Value::String)),
+            ("minItems".into(), c.min_items.clone()),
+            ("maxItems".into(), c.max_items.clone()),
+        ];
+        for (key, value) in keywords {
+            if let Some(v) = value {
+                s[key] = v;
+            }
+        }
+        if c.unique_items {
+            s["uniqueItems"] = json!(true);
+        }
+    }
+
+    annotate(&mut s, model);
+    s
+}
+
+// adds annotation keywords
+fn annotate(s: &mut Value, model: &Model) {
+    if let Some(description) = model.description.as_ref() {
+        s["description"] = json!(description);
+    }
+    if let Some(default) = model.default.as_ref() {
+        s["default"] = default.clone();
+    }
+    if model.readonly {
+        s["readOnly"] = json!(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_property_schema() {
+        let model = Model {
+            name: "owner".into(),
+            def: "Owner".into(),
+            nullable: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            property_schema(&model, "Pet"),
+            json!({ "anyOf": [{ "$ref": "#/$defs/Owner" }, { "type": "null" }] })
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use super::assets::Assets;
+use super::error::{Error, Result};
 use super::util;
 use super::Model;
-use failure::Fallible;
 use handlebars::Handlebars;
 use handlebars::*;
 use itertools::Itertools;
+use log::warn;
 use maplit::hashmap;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -25,12 +26,38 @@ pub struct Lang {
     pub paths: HashMap<String, String>,
     #[serde(default)]
     pub reserved: Vec<String>,
+    /// Assigns stable `x-proto-field` numbers to fields, see `Config.lockfile`
+    #[serde(default)]
+    pub field_numbers: bool,
+    /// Arrays can't be nested in arrays or used as map values (protobuf `repeated`),
+    /// such schemas are rejected
+    #[serde(default)]
+    pub flat_arrays: bool,
+    /// SQL dialect for migrations: `postgresql`, `mysql` or `sqlite`
+    pub dialect: Option<String>,
+    /// Built-in lang this spec was copied from, see `Assets::eject`
+    pub ejected_from: Option<Ejected>,
+}
+
+/// Origin of an ejected built-in lang
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Ejected {
+    /// Built-in lang name
+    pub lang: String,
+    /// Ram version the lang was ejected from
+    pub version: String,
+    /// Hashes of built-in files at the time of ejecting, by file name
+    #[serde(default)]
+    pub hashes: HashMap<String, String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AddFile {
     pub filename: Option<String>,
+    #[serde(default)]
     pub template: String,
+    /// Name of a built-in emitter, used instead of template
+    pub emitter: Option<String>,
     #[serde(rename = "in")]
     pub file_in: Option<String>,
     pub path: Option<String>,
@@ -50,19 +77,16 @@ pub struct Format {
 }
 
 impl Lang {
-    pub fn load_file(path: &Path) -> Fallible<Self> {
+    pub fn load_file(path: &Path) -> Result<Self> {
         let mut pathbuf = path.to_owned();
         let data = {
             // if no extension, assume its one of the built-in specs
             if path.extension().is_none() {
                 // load from assets
-                pathbuf = PathBuf::from(&format!(
-                    "{lang}/{lang}.yaml",
-                    lang = &path.to_str().unwrap()
-                ));
+                pathbuf = PathBuf::from(&format!("{lang}/{lang}.yaml", lang = path.display()));
             }
 
-            Assets::read_file(&PathBuf::from(&pathbuf))?
+            Assets::read_file(&pathbuf)?
         };
 
         let ext = path
@@ -71,31 +95,40 @@ impl Lang {
             .unwrap_or("yaml");
 
         let mut lang: Self = match ext {
-            "yaml" | "yml" => serde_yaml::from_str(&data)?,
-            "json" | _ => serde_json::from_str(&data)?,
+            "yaml" | "yml" => serde_yaml::from_str(&data).map_err(|e| Error::file(&pathbuf, e))?,
+            "json" | _ => serde_json::from_str(&data).map_err(|e| Error::file(&pathbuf, e))?,
         };
 
         // set lang spec path
-        lang.path = pathbuf
-            .parent()
-            .expect("failed to get lang parent dir")
-            .to_owned();
+        lang.path = pathbuf.parent().map(Path::to_path_buf).unwrap_or_default();
 
         // set root path to "" if not set
         if lang.paths.get("root") == None {
             lang.paths.insert("root".into(), "".into());
         }
 
+        // built-in files may have changed since lang was ejected
+        if let Some(ref ejected) = lang.ejected_from {
+            let changed = Assets::changed_since_eject(ejected);
+            if !changed.is_empty() {
+                warn!(
+                    "{} was ejected from ram {}, built-in {} lang has changed since: {}",
+                    pathbuf.display(),
+                    ejected.version,
+                    ejected.lang,
+                    changed.join(", ")
+                );
+            }
+        }
+
         Ok(lang)
     }
 
-    pub fn default_path(&self, path: &str) -> PathBuf {
-        PathBuf::from(
-            &self
-                .paths
-                .get(path)
-                .expect(&format!("failed to find default path: {}", path)),
-        )
+    pub fn default_path(&self, path: &str) -> Result<PathBuf> {
+        self.paths
+            .get(path)
+            .map(PathBuf::from)
+            .ok_or_else(|| Error::config(&format!("paths.{}", path), "path is not defined"))
     }
 
     // Returns vec of additional files, with joined relative paths
@@ -104,8 +137,7 @@ impl Lang {
             .iter()
             .map(|af| AddFile {
                 template: util::join_relative(&self.path, &PathBuf::from(&af.template))
-                    .to_str()
-                    .unwrap()
+                    .to_string_lossy()
                     .into(),
                 ..af.clone()
             })
@@ -115,7 +147,7 @@ impl Lang {
     /*
      * Formatter functions
      */
-    pub fn format(&self, template_key: &str, value: &String) -> Fallible<String> {
+    pub fn format(&self, template_key: &str, value: &String) -> Result<String> {
         match template_key {
             "r" if !self.reserved.contains(&value) => Ok(value.clone()),
             _ => {
@@ -133,7 +165,8 @@ impl Lang {
         self.helpers
             .get(template_key)
             .and_then(|template| hb.render_template(template, map).ok())
-            .unwrap_or_else(|| map.get("value").unwrap().to_string())
+            .or_else(|| map.get("value").map(|v| v.to_string()))
+            .unwrap_or_default()
     }
 
     // applies `classname` and `object_property` to input str
@@ -146,32 +179,49 @@ impl Lang {
     }
 
     // translates to array type by child item
-    pub fn translate_array(&self, m: &Model) -> String {
+    pub fn translate_array(&self, m: &Model) -> Result<String> {
         // translate child
         let child = m
             .items
             .as_ref()
-            .expect("array child type is None")
+            .ok_or_else(|| Error::spec("", "array is missing items"))?
             .clone()
-            .translate(self);
+            .translate(self)
+            .map_err(|e| e.within(&["items"]))?;
+        if self.flat_arrays && child.is_array {
+            return Err(Error::spec(
+                "/items",
+                "nested arrays are not supported by lang, wrap items in an object",
+            ));
+        }
         // array formatter
-        self.format_map(
+        Ok(self.format_map(
             "array",
-            &hashmap!["value" => m.name.as_str(), "type" => child.schema_type.as_str(), "name" => m.name.as_str()],
-        )
+            &hashmap![
+                "value" => m.name.as_str(),
+                "type" => child.schema_type.as_str(),
+                "name" => m.name.as_str(),
+                "nullable" => if child.nullable { "true" } else { "" },
+            ],
+        ))
     }
 
     // returns translated primitive type
-    pub fn translate_primitive(&self, schema_type: &String, format: &String) -> String {
+    pub fn translate_primitive(&self, schema_type: &String, format: &String) -> Result<String> {
         self.types
             .iter()
             .find(|(name, t)| *name == schema_type || t.alias.contains(schema_type))
             .and_then(|(_, t)| t.format.get(format).or_else(|| t.format.get("default")))
             .map(|f| f.schema_type.clone())
-            .expect(&format!(
-                "Error while processing {}: failed to find primitive type {}",
-                schema_type, format
-            ))
+            .ok_or_else(|| {
+                Error::spec(
+                    "/type",
+                    format!(
+                        "type '{}' with format '{}' is not defined in lang spec",
+                        schema_type, format
+                    ),
+                )
+            })
     }
 
     // adds helpers to handlebars instance
@@ -190,7 +240,9 @@ impl Lang {
                 let param = h
                     .param(0)
                     .and_then(|v| v.value().as_str())
-                    .expect("parameter is missing")
+                    .ok_or_else(|| {
+                        RenderError::new(&format!("helper '{}': parameter is missing", key))
+                    })?
                     .to_string();
                 out.write(&lang.format(&key, &param).unwrap_or(param))?;
                 Ok(())
@@ -202,7 +254,7 @@ impl Lang {
     /// Formats all path paramers in form of {param} with given formatter if any
     pub fn format_path(&self, p: String) -> String {
         // TODO: clean this mess
-        let re = Regex::new(r"^\{(\w+)\}$").unwrap();
+        let re = Regex::new(r"^\{([^}]+)\}$").unwrap();
         self.helpers
             .get("pathparam")
             .map(|_| {
@@ -211,11 +263,11 @@ impl Lang {
                     &Path::new(&p)
                         .iter()
                         .skip(1) // leave out preceding '/', which is in the standard
-                        .map(|part| part.to_str().unwrap())
+                        .map(|part| part.to_string_lossy())
                         .map(|part| {
-                            if let Some(cap) = re.captures_iter(part).next() {
+                            if let Some(cap) = re.captures_iter(&part).next() {
                                 self.format("pathparam", &cap[1].to_owned())
-                                    .unwrap_or(part.to_string())
+                                    .unwrap_or_else(|_| part.to_string())
                             } else {
                                 part.to_string()
                             }
@@ -1,30 +1,62 @@
 mod assets;
+mod body;
+mod check;
 mod config;
+mod dts;
+mod emitter;
+mod error;
 mod helper;
+mod ignore;
+mod init;
+mod jsonschema;
 mod lang;
+mod lock;
+mod manifest;
+mod merge;
+mod migration;
 mod model;
 mod param;
+mod regions;
 mod resource;
+mod response;
+pub mod spec;
 mod state;
+mod swagger;
 pub mod util;
+pub mod watch;
 
-use assets::Assets;
+pub use assets::Assets;
+pub use body::Body;
+pub use check::{check_files, FileCheck};
 pub use config::Config;
-pub use lang::{AddFile, Lang};
+pub use emitter::Emitter;
+pub use error::{Error, Result};
+pub use ignore::Ignore;
+pub use init::{init_files, set_config_lang};
+pub use lang::{AddFile, Ejected, Lang};
+pub use lock::FieldLock;
+pub use manifest::{Manifest, ManifestEntry};
+pub use merge::{merge_specs, ConflictPolicy, SpecModels};
+pub use migration::Migration;
 pub use model::{Model, ModelType};
 pub use param::Param;
 pub use resource::{GroupingStrategy, Resource, ResourceGroup};
+pub use response::{Content, Response};
 pub use state::State;
 
 use handlebars::Handlebars;
-use log::info;
-use openapi::v3_0::Spec;
-use serde_json::json;
+use indexmap::IndexMap;
+use log::{info, warn};
+use openapi::v3_0::{PathItem, Spec};
+use spec::Webhooks;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+/// Ram version, recorded in ejected lang specs
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[allow(dead_code)]
-fn normalize_models(models: Vec<Model>) -> Vec<Model> {
+fn normalize_models(models: Vec<Model>) -> Result<Vec<Model>> {
     // map top-level models by name
     let models_map = models
         .iter()
@@ -38,50 +70,169 @@ fn normalize_models(models: Vec<Model>) -> Vec<Model> {
         .collect()
 }
 
-pub fn generate_models_v3(spec: &Spec, root: &Path) -> Vec<Model> {
+// merges allOf parent properties into models,
+// resolves references to enum models and polymorphic parents of variants
+fn resolve_models(models: Vec<Model>) -> Vec<Model> {
+    // map top-level models by name
+    let models_map = models
+        .iter()
+        .map(|m| (m.def.clone(), m.clone()))
+        .collect::<HashMap<String, Model>>();
+
+    models
+        .into_iter()
+        .map(|m| {
+            m.inherit(&models_map)
+                .resolve_enums(&models_map)
+                .resolve_implements(&models_map)
+        })
+        .collect()
+}
+
+pub fn generate_models_v3(spec: &Spec, root: &Path) -> Result<Vec<Model>> {
+    generate_models_with_webhooks_v3(spec, &Webhooks::new(), root)
+}
+
+/// Generates models like `generate_models_v3`, including inline request bodies of webhooks
+pub fn generate_models_with_webhooks_v3(
+    spec: &Spec,
+    webhooks: &Webhooks,
+    root: &Path,
+) -> Result<Vec<Model>> {
     // iterate components
     // + generate models
-    util::collect_schemas(spec, root)
-        .expect("failed to collect schemas")
+    let mut models: Vec<Model> = util::collect_schemas(spec, root)?
         .iter()
         .map(|(key, schema)| Model::new(key, schema, key))
-        .collect()
+        .collect();
+
+    // inline object variants of polymorphic models are named after their position
+    let variants: Vec<Model> = models.iter().flat_map(Model::inline_variants).collect();
+    models.extend(variants);
+
+    // inline request bodies are named after their operations
+    let request_bodies = util::collect_request_bodies(spec, root)?;
+    models.extend(body::inline_body_models(&spec.paths, &request_bodies));
+    models.extend(body::inline_body_models(webhooks, &request_bodies));
+
+    // resolve allOf compositions and enum refs
+    Ok(resolve_models(models))
 }
 
 pub fn generate_resources_v3(
     spec: &Spec,
     root: &Path,
     grouping_strategy: GroupingStrategy,
-) -> Vec<ResourceGroup> {
-    let parameters_map =
-        util::collect_parameters(spec, root).expect("failed to collect parameters");
-    resource::group_resources(&spec.paths, grouping_strategy, &parameters_map)
+) -> Result<Vec<ResourceGroup>> {
+    group_resources_v3(spec, &spec.paths, root, grouping_strategy, false)
+}
+
+/// Generates resource groups of webhooks, resource paths are webhook names
+pub fn generate_webhooks_v3(
+    spec: &Spec,
+    webhooks: &Webhooks,
+    root: &Path,
+    grouping_strategy: GroupingStrategy,
+) -> Result<Vec<ResourceGroup>> {
+    group_resources_v3(spec, webhooks, root, grouping_strategy, true)
+}
+
+fn group_resources_v3(
+    spec: &Spec,
+    paths: &IndexMap<String, PathItem>,
+    root: &Path,
+    grouping_strategy: GroupingStrategy,
+    is_webhook: bool,
+) -> Result<Vec<ResourceGroup>> {
+    let parameters_map = util::collect_parameters(spec, root)?;
+    let request_bodies_map = util::collect_request_bodies(spec, root)?;
+    let headers_map = util::collect_headers(spec, root)?;
+    resource::group_resources(
+        paths,
+        grouping_strategy,
+        &parameters_map,
+        &request_bodies_map,
+        &headers_map,
+        is_webhook,
+    )
 }
 
 /// Creates ready to use state value with translated models
 pub fn create_state(
+    cfg: Config,
+    models: Vec<Model>,
+    resource_groups: Vec<ResourceGroup>,
+    no_defaults: bool,
+) -> Result<State> {
+    create_state_with_webhooks(cfg, models, resource_groups, vec![], None, no_defaults)
+}
+
+/// Same as `create_state`, also translates webhook resource groups.
+/// If lang numbers fields, numbers in `field_lock` are kept and new fields are numbered
+/// after them. Without a lock fields are numbered from scratch and nothing is written
+/// by `write_lockfile`.
+pub fn create_state_with_webhooks(
     cfg: Config,
     mut models: Vec<Model>,
     mut resource_groups: Vec<ResourceGroup>,
+    mut webhook_groups: Vec<ResourceGroup>,
+    field_lock: Option<FieldLock>,
     no_defaults: bool,
-) -> State {
+) -> Result<State> {
     // get lang config
-    let lang = cfg.get_lang().expect("failed to create lang spec!");
+    let lang = cfg.get_lang()?;
+
+    // number fields, keeping numbers from lock
+    let field_lock = if lang.field_numbers {
+        let locked = field_lock.is_some();
+        let mut lock = field_lock.unwrap_or_default();
+        lock.assign_models(&mut models)?;
+        lock.assign_enums(&mut models)?;
+        lock.assign_resources(&mut resource_groups)?;
+        lock.assign_resources(&mut webhook_groups)?;
+        if locked {
+            Some(lock)
+        } else {
+            None
+        }
+    } else {
+        None
+    };
 
     // translate and format models and resource groups
-    models = translate_models(&lang, models);
-    resource_groups = translate_resource_groups(&lang, resource_groups);
+    models = translate_models(&lang, models)?;
+    resource_groups = translate_resource_groups(&lang, resource_groups)?;
+    webhook_groups = translate_resource_groups(&lang, webhook_groups)?;
 
-    State {
+    Ok(State {
         cfg,
         models,
         resource_groups,
+        webhook_groups,
         lang,
         no_defaults,
-    }
+        field_lock,
+    })
 }
 
-pub fn generate_files(state: State) -> HashMap<PathBuf, String> {
+pub fn generate_files(state: State) -> Result<HashMap<PathBuf, String>> {
+    generate_files_with_sources(state).map(|(files, _)| files)
+}
+
+/// Same as `generate_files`, also returns the template source of each file,
+/// used in manifest
+pub fn generate_files_with_sources(
+    state: State,
+) -> Result<(HashMap<PathBuf, String>, HashMap<PathBuf, String>)> {
+    generate_files_with_emitters(state, &[])
+}
+
+/// Same as `generate_files_with_sources`, `files` can also refer to given emitters by name.
+/// Given emitters take precedence over built-in emitters with the same name
+pub fn generate_files_with_emitters(
+    state: State,
+    emitters: &[(&str, &dyn Emitter)],
+) -> Result<(HashMap<PathBuf, String>, HashMap<PathBuf, String>)> {
     info!("Generating files...");
     let mut hb = Handlebars::new();
     util::init_handlebars(&mut hb);
@@ -97,18 +248,103 @@ pub fn generate_files(state: State) -> HashMap<PathBuf, String> {
     });
 
     info!("Rendering templates...");
-    render_files(&mut hb, &state, files)
+    render_files(&mut hb, &state, files, emitters)
+}
+
+/// Writes updated field numbers to `cfg.lockfile`, if lang numbers fields.
+/// Lockfile is not a generated file, so it's kept out of checks and manifest.
+pub fn write_lockfile(state: &State) -> Result<()> {
+    match (&state.field_lock, state.cfg.get_lockfile()) {
+        (Some(lock), Some(path)) => lock.write_file(&path),
+        _ => Ok(()),
+    }
+}
+
+/// Diffs sql tables of previous and current state, returns up and down migration files
+/// named `<timestamp>_<name>.{up,down}.sql` under `migrations` path.
+/// Empty map is returned if tables have not changed.
+/// Changes the sql dialect can't express are logged as warnings.
+pub fn generate_migration(
+    previous: &State,
+    state: &State,
+    name: &str,
+) -> Result<HashMap<PathBuf, String>> {
+    let migration = migration::diff(&previous.models, &state.models, &state.lang)?;
+    if migration.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    // migration is still written, tables are left to be rebuilt by hand
+    for note in migration.unsupported.iter() {
+        warn!("{}", note);
+    }
+
+    let dirpath = state.cfg.get_path("migrations", &state.lang)?;
+    let prefix = format!("{}_{}", migration::timestamp(), name);
+    let mut files = HashMap::new();
+    files.insert(dirpath.join(format!("{}.up.sql", prefix)), migration.up);
+    files.insert(dirpath.join(format!("{}.down.sql", prefix)), migration.down);
+    Ok(files)
+}
+
+/// Checks that files of config and lang can be rendered without rendering them:
+/// templates exist and compile, emitters and output paths are defined.
+/// Returns found problems.
+pub fn validate_files(state: &State) -> Vec<Error> {
+    validate_files_with_emitters(state, &[])
+}
+
+/// Same as `validate_files`, given emitters are known besides built-in ones
+pub fn validate_files_with_emitters(
+    state: &State,
+    emitters: &[(&str, &dyn Emitter)],
+) -> Vec<Error> {
+    let files = state.cfg.get_files(match state.no_defaults {
+        false => Some(&state.lang),
+        _ => None,
+    });
+
+    let mut errors = vec![];
+    for f in files {
+        if let Some(ref inpath) = f.file_in {
+            errors.extend(state.cfg.get_path(inpath, &state.lang).err());
+        }
+        match f.emitter {
+            Some(ref name) if emitter::find(name, emitters).is_none() => errors.push(
+                Error::config("files", format!("unknown emitter '{}'", name)),
+            ),
+            Some(_) => {}
+            None => {
+                let compiled = Assets::read_file(&PathBuf::from(&f.template))
+                    .map_err(|e| Error::template(&f.template, e))
+                    .and_then(|template| {
+                        Handlebars::new()
+                            .register_template_string(&f.template, template)
+                            .map_err(|e| Error::template(&f.template, e))
+                    });
+                errors.extend(compiled.err());
+            }
+        }
+    }
+    errors
 }
 
 // runs lang translations on all models
-fn translate_models(lang: &Lang, models: Vec<Model>) -> Vec<Model> {
-    models.into_iter().map(|m| m.translate(lang)).collect()
+fn translate_models(lang: &Lang, models: Vec<Model>) -> Result<Vec<Model>> {
+    models
+        .into_iter()
+        .map(|m| {
+            let def = m.def.clone();
+            m.translate(lang)
+                .map_err(|e| e.within(&["components", "schemas", &def]))
+        })
+        .collect()
 }
 
 fn translate_resource_groups(
     lang: &Lang,
     resource_groups: Vec<ResourceGroup>,
-) -> Vec<ResourceGroup> {
+) -> Result<Vec<ResourceGroup>> {
     resource_groups
         .into_iter()
         // run format on all resources
@@ -118,8 +354,8 @@ fn translate_resource_groups(
                 .resources
                 .into_iter()
                 .map(|r| r.translate(lang))
-                .collect();
-            rg2
+                .collect::<Result<_>>()?;
+            Ok(rg2)
         })
         .collect()
 }
@@ -129,39 +365,58 @@ fn render_files(
     hb: &mut Handlebars,
     state: &State,
     files: Vec<AddFile>,
-) -> HashMap<PathBuf, String> {
+    emitters: &[(&str, &dyn Emitter)],
+) -> Result<(HashMap<PathBuf, String>, HashMap<PathBuf, String>)> {
     // state to serde json value
-    let statejson = json!(&state);
+    let statejson = serde_json::to_value(&state).map_err(Error::serialize)?;
 
-    // render files
-    files
-        .into_iter()
-        .flat_map(|f: AddFile| {
-            // get data from assets and render it
-            let template = Assets::read_file(&PathBuf::from(&f.template)).unwrap();
-            let render = hb
-                .render_template(&template, &statejson)
-                .expect("failed to render additional file template");
-            // make path
-            let dirpath: PathBuf = if let Some(ref abspath) = f.path {
-                // get from absolute path
-                PathBuf::from(abspath)
-            } else if let Some(ref inpath) = f.file_in {
-                // get location from 'in' using config.files
-                let path = state.cfg.get_path(inpath, &state.lang);
-                path
-            } else {
-                // use rootpath
-                let path = state.cfg.get_path("root", &state.lang);
-                path
-            };
-
-            // If file name is defined, use it as output for file.
-            // If not, then assume the filenames are found inside the templates
-            match f.filename {
-                Some(filename) => vec![(dirpath.join(filename), render)],
-                _ => util::split_files(render, dirpath),
+    // render files, keeping track of their sources
+    let mut rendered = HashMap::new();
+    let mut sources = HashMap::new();
+    for f in files {
+        // make path
+        let dirpath: PathBuf = if let Some(ref abspath) = f.path {
+            // get from absolute path
+            PathBuf::from(abspath)
+        } else if let Some(ref inpath) = f.file_in {
+            // get location from 'in' using config.files
+            state.cfg.get_path(inpath, &state.lang)?
+        } else {
+            // use rootpath
+            state.cfg.get_path("root", &state.lang)?
+        };
+
+        // emitters produce files directly from state
+        if let Some(ref name) = f.emitter {
+            let emitter = emitter::find(name, emitters)
+                .ok_or_else(|| Error::config("files", format!("unknown emitter '{}'", name)))?;
+            let files = emitter
+                .emit(state, f.filename.as_deref())
+                .map_err(|e| Error::template(name, e))?;
+            for (file, data) in files {
+                sources.insert(dirpath.join(&file), format!("emitter:{}", name));
+                rendered.insert(dirpath.join(file), data);
             }
-        })
-        .collect()
+            continue;
+        }
+
+        // get data from assets and render it
+        let template = Assets::read_file(&PathBuf::from(&f.template))?;
+        let render = hb
+            .render_template(&template, &statejson)
+            .map_err(|e| Error::template(&f.template, e))?;
+
+        // If file name is defined, use it as output for file.
+        // If not, then assume the filenames are found inside the templates
+        let files = match f.filename {
+            Some(filename) => vec![(dirpath.join(filename), render)],
+            _ => util::split_files(render, dirpath),
+        };
+        for (path, data) in files {
+            sources.insert(path.clone(), f.template.clone());
+            rendered.insert(path, data);
+        }
+    }
+
+    Ok((rendered, sources))
 }
@@ -0,0 +1,252 @@
+use super::error::{Error, Result};
+use super::{Model, ResourceGroup};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Extension holding the field number of a property
+pub const FIELD_EXTENSION: &str = "x-proto-field";
+/// Extension holding numbers and names of removed fields of a model
+pub const RESERVED_EXTENSION: &str = "x-proto-reserved";
+
+// protobuf implementation reserved field numbers
+const IMPLEMENTATION_RESERVED: std::ops::RangeInclusive<u64> = 19000..=19999;
+
+/// Field numbers by message and field name.
+/// Numbers of removed fields are kept, so they are never reused.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct FieldLock(IndexMap<String, IndexMap<String, u64>>);
+
+impl FieldLock {
+    /// Reads lockfile, missing file results in an empty lock
+    pub fn load_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = std::fs::read_to_string(path).map_err(|e| Error::file(path, e))?;
+        serde_json::from_str(&data).map_err(|e| Error::file(path, e))
+    }
+
+    pub fn write_file(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_string_pretty(self).map_err(|e| Error::file(path, e))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Error::file(parent, e))?;
+        }
+        std::fs::write(path, data).map_err(|e| Error::file(path, e))
+    }
+
+    /// Sets field numbers to model properties, `oneOf` variants and additional properties
+    pub fn assign_models(&mut self, models: &mut [Model]) -> Result<()> {
+        for model in models
+            .iter_mut()
+            .filter(|m| m.is_object || m.is_polymorphic)
+        {
+            let message = model.name.clone();
+            let mut fields: Vec<&mut Model> = if model.is_polymorphic {
+                model.variants.iter_mut().map(|m| m.as_mut()).collect()
+            } else {
+                model.properties.iter_mut().map(|m| m.as_mut()).collect()
+            };
+            if let Some(additional) = model.additional_properties.as_mut() {
+                additional.name = "additionalProperties".into();
+                fields.push(additional.as_mut());
+            }
+
+            let reserved = self
+                .assign(&message, &mut fields)
+                .map_err(|e| e.within(&["components", "schemas", &message]))?;
+            if !reserved.is_empty() {
+                model
+                    .extensions
+                    .insert(RESERVED_EXTENSION.into(), Value::Array(reserved));
+            }
+            model.apply_properties();
+        }
+        Ok(())
+    }
+
+    /// Sets numbers to values of enum models, starting from 1 as 0 is left for the default value
+    pub fn assign_enums(&mut self, models: &mut [Model]) -> Result<()> {
+        for model in models.iter_mut().filter(|m| m.is_enum) {
+            let values: Vec<(String, Option<u64>)> = model
+                .enum_values
+                .iter()
+                .map(|ev| (ev.variant.clone(), None))
+                .collect();
+            let (numbers, reserved) = self
+                .number(&model.name, &values)
+                .map_err(|e| e.within(&["components", "schemas", &model.name]))?;
+            for (ev, number) in model.enum_values.iter_mut().zip(numbers) {
+                ev.number = Some(number);
+            }
+            if !reserved.is_empty() {
+                model
+                    .extensions
+                    .insert(RESERVED_EXTENSION.into(), Value::Array(reserved));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets field numbers to request parameters and body, messages are named `<operation>Request`
+    pub fn assign_resources(&mut self, resource_groups: &mut [ResourceGroup]) -> Result<()> {
+        for resource in resource_groups
+            .iter_mut()
+            .flat_map(|rg| rg.resources.iter_mut())
+        {
+            let message = format!("{}Request", resource.name);
+            let mut fields: Vec<&mut Model> = resource
+                .path_params
+                .iter_mut()
+                .chain(resource.query_params.iter_mut())
+                .map(|p| &mut p.model)
+                .chain(resource.body.iter_mut().map(|b| &mut b.model))
+                .collect();
+            let root = if resource.is_webhook {
+                "webhooks"
+            } else {
+                "paths"
+            };
+            self.assign(&message, &mut fields)
+                .map_err(|e| e.within(&[root, &resource.path]))?;
+        }
+        Ok(())
+    }
+
+    // assigns numbers for fields of a message,
+    // explicit `x-proto-field` numbers take precedence over locked ones.
+    // Returns reserved (removed) fields
+    fn assign(&mut self, message: &str, fields: &mut [&mut Model]) -> Result<Vec<Value>> {
+        let names: Vec<(String, Option<u64>)> = fields
+            .iter()
+            .map(|f| {
+                let explicit = f.extensions.get(FIELD_EXTENSION).and_then(Value::as_u64);
+                (f.name.clone(), explicit)
+            })
+            .collect();
+        let (numbers, reserved) = self.number(message, &names)?;
+        for (field, number) in fields.iter_mut().zip(numbers) {
+            field
+                .extensions
+                .insert(FIELD_EXTENSION.into(), json!(number));
+        }
+        Ok(reserved)
+    }
+
+    // numbers named fields of a message, keeping locked numbers.
+    // Returns numbers in order of fields and reserved (removed) fields
+    fn number(
+        &mut self,
+        message: &str,
+        fields: &[(String, Option<u64>)],
+    ) -> Result<(Vec<u64>, Vec<Value>)> {
+        let locked = self.0.entry(message.to_string()).or_default();
+
+        for (name, number) in fields.iter() {
+            if let Some(number) = number {
+                let taken = locked
+                    .iter()
+                    .find(|(other, n)| *n == number && *other != name)
+                    .map(|(other, _)| other.clone());
+                match taken {
+                    Some(other) if fields.iter().any(|(f, _)| *f == other) => {
+                        return Err(Error::spec(
+                            &format!("/properties/{}/{}", name, FIELD_EXTENSION),
+                            format!("field number {} is already used by '{}'", number, other),
+                        ));
+                    }
+                    // number of a removed field can be reused explicitly
+                    Some(other) => {
+                        locked.shift_remove(&other);
+                    }
+                    None => {}
+                }
+                locked.insert(name.clone(), *number);
+            }
+        }
+
+        let mut next = locked.values().max().cloned().unwrap_or(0) + 1;
+        for (name, _) in fields.iter() {
+            if !locked.contains_key(name) {
+                if IMPLEMENTATION_RESERVED.contains(&next) {
+                    next = IMPLEMENTATION_RESERVED.end() + 1;
+                }
+                locked.insert(name.clone(), next);
+                next += 1;
+            }
+        }
+
+        let numbers = fields.iter().map(|(name, _)| locked[name]).collect();
+        let reserved = locked
+            .iter()
+            .filter(|(name, _)| !fields.iter().any(|(f, _)| f == *name))
+            .map(|(name, number)| json!({ "name": name, "number": number }))
+            .collect();
+        Ok((numbers, reserved))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::EnumValue;
+
+    fn field(name: &str) -> Model {
+        Model {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_assign() {
+        let mut lock = FieldLock::default();
+        let (mut a, mut b) = (field("a"), field("b"));
+        lock.assign("Msg", &mut [&mut a, &mut b]).unwrap();
+        assert_eq!(b.extensions[FIELD_EXTENSION], json!(2));
+
+        // removed field is reserved, new field gets next number
+        let (mut b, mut c) = (field("b"), field("c"));
+        let reserved = lock.assign("Msg", &mut [&mut c, &mut b]).unwrap();
+        assert_eq!(b.extensions[FIELD_EXTENSION], json!(2));
+        assert_eq!(c.extensions[FIELD_EXTENSION], json!(3));
+        assert_eq!(reserved, vec![json!({ "name": "a", "number": 1 })]);
+
+        // explicit number can't clash with existing field
+        let mut d = field("d");
+        d.extensions.insert(FIELD_EXTENSION.into(), json!(3));
+        assert!(lock.assign("Msg", &mut [&mut b, &mut c, &mut d]).is_err());
+    }
+
+    #[test]
+    fn test_assign_enums() {
+        let value = |v: &str| EnumValue {
+            value: json!(v),
+            variant: v.into(),
+            number: None,
+        };
+        let color = |values: Vec<EnumValue>| Model {
+            name: "Color".into(),
+            is_enum: true,
+            enum_values: values,
+            ..Default::default()
+        };
+
+        let mut lock = FieldLock::default();
+        let mut models = vec![color(vec![value("red"), value("blue")])];
+        lock.assign_enums(&mut models).unwrap();
+        let numbers: Vec<Option<u64>> = models[0].enum_values.iter().map(|v| v.number).collect();
+        assert_eq!(numbers, vec![Some(1), Some(2)]);
+
+        // numbers are kept when values are reordered, removed ones are reserved
+        let mut models = vec![color(vec![value("green"), value("blue")])];
+        lock.assign_enums(&mut models).unwrap();
+        let numbers: Vec<Option<u64>> = models[0].enum_values.iter().map(|v| v.number).collect();
+        assert_eq!(numbers, vec![Some(3), Some(2)]);
+        assert_eq!(
+            models[0].extensions[RESERVED_EXTENSION],
+            json!([{ "name": "red", "number": 1 }])
+        );
+    }
+}
@@ -1,35 +1,182 @@
-use log::{info, LevelFilter};
-use ram::{Config, GroupingStrategy};
-use std::panic;
-use std::path::PathBuf;
+use log::{error, info, LevelFilter};
+use ram::util::FileChange;
+use ram::{
+    Assets, Config, ConflictPolicy, Error, FieldLock, FileCheck, GroupingStrategy, Ignore, Lang,
+    Manifest, SpecModels, State,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "ram", about = "openapi generator")]
 struct Arguments {
-    /// ram configuration file path
-    #[structopt(short, long)]
-    config: PathBuf,
+    /// quiet logging level
+    #[structopt(short, long, global = true)]
+    quiet: bool,
+
+    #[structopt(subcommand)]
+    command: Command,
+}
 
-    /// input openapi spec file
+/// Spec and config options
+#[derive(Debug, StructOpt)]
+struct Input {
+    /// ram configuration file path
     #[structopt(short, long)]
-    input: PathBuf,
+    config: Option<PathBuf>,
 
-    /// output path
+    /// built-in lang or lang spec path, used when no config is given
     #[structopt(short, long)]
-    output: Option<PathBuf>,
+    lang: Option<String>,
 
-    /// prints state passed to templates as json
+    /// input openapi spec file, can be given several times to merge specs.
+    /// Defaults to config specs
     #[structopt(short, long)]
-    json: bool,
+    input: Vec<PathBuf>,
 
     /// skips generating default asset files
     #[structopt(short, long)]
     no_defaults: bool,
+}
 
-    /// quiet logging level
+/// Output options
+#[derive(Debug, StructOpt)]
+struct Output {
+    /// output path
     #[structopt(short, long)]
-    quiet: bool,
+    output: PathBuf,
+
+    /// ignore file to use instead of `.ramignore` in output and config directories
+    #[structopt(long)]
+    ignore_file: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// generates files from spec
+    Generate {
+        #[structopt(flatten)]
+        input: Input,
+        #[structopt(flatten)]
+        output: Output,
+        /// lists files that would be created or modified, without writing them
+        #[structopt(long)]
+        dry_run: bool,
+        /// manifest file of generated files, stale files of previous manifest are removed
+        #[structopt(long)]
+        manifest: Option<PathBuf>,
+    },
+    /// prints state passed to templates as json
+    State {
+        #[structopt(flatten)]
+        input: Input,
+    },
+    /// loads spec, config and lang, and reports problems without rendering
+    Validate {
+        #[structopt(flatten)]
+        input: Input,
+    },
+    /// lists built-in langs with their files and paths
+    Langs,
+    /// creates a config and copies of built-in templates for customizing
+    Init {
+        /// built-in lang
+        lang: String,
+        /// directory to create files in
+        #[structopt(short, long, default_value = ".")]
+        output: PathBuf,
+    },
+    /// copies spec and templates of a built-in lang, and points config lang at the copied spec
+    Eject {
+        /// built-in lang
+        lang: String,
+        /// directory to copy lang files to, defaults to lang name
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+        /// config to update, created if it does not exist
+        #[structopt(short, long, default_value = "config.yaml")]
+        config: PathBuf,
+        /// overwrite existing files in output directory
+        #[structopt(long)]
+        force: bool,
+    },
+    /// compares generated files against files in output, exits with error if they differ
+    Check {
+        #[structopt(flatten)]
+        input: Input,
+        #[structopt(flatten)]
+        output: Output,
+    },
+    /// regenerates files whenever spec, config, lang spec or templates change
+    Watch {
+        #[structopt(flatten)]
+        input: Input,
+        #[structopt(flatten)]
+        output: Output,
+        /// manifest file of generated files, stale files of previous manifest are removed
+        #[structopt(long)]
+        manifest: Option<PathBuf>,
+        /// polling interval in milliseconds
+        #[structopt(long, default_value = "500")]
+        interval: u64,
+    },
+    /// writes sql migration by diffing tables of previous and current spec
+    Migrate {
+        #[structopt(flatten)]
+        input: Input,
+        #[structopt(flatten)]
+        output: Output,
+        /// previous spec or state json (from `state`)
+        #[structopt(long)]
+        from: PathBuf,
+        /// name of generated migration
+        #[structopt(long, default_value = "migration")]
+        name: String,
+    },
+}
+
+impl Input {
+    // loads config file, or uses default config for lang
+    fn load_config(&self) -> ram::Result<Config> {
+        match (&self.config, &self.lang) {
+            (Some(path), _) => Config::load_file(path),
+            (None, Some(lang)) => Ok(Config {
+                lang: lang.clone(),
+                path: std::env::current_dir().unwrap_or_default(),
+                ..Default::default()
+            }),
+            (None, None) => Err(Error::config("lang", "either config or lang is required")),
+        }
+    }
+
+    // returns input specs given on command line, or specs of config
+    fn inputs(&self, cfg: &Config) -> ram::Result<Vec<PathBuf>> {
+        let inputs = match self.input.is_empty() {
+            true => cfg.get_specs(),
+            _ => self.input.clone(),
+        };
+        match inputs.is_empty() {
+            true => Err(Error::config("specs", "no input specs given")),
+            _ => Ok(inputs),
+        }
+    }
+
+    fn load_state(&self) -> ram::Result<State> {
+        let cfg = self.load_config()?;
+        let inputs = self.inputs(&cfg)?;
+        load_state(cfg, &inputs, self.no_defaults)
+    }
+}
+
+impl Output {
+    // returns ignore rules from override file or output and config directories
+    fn load_ignore(&self, cfg: &Config) -> ram::Result<Ignore> {
+        match self.ignore_file {
+            Some(ref path) => Ignore::load_file(path),
+            None => Ok(Ignore::lookup(&self.output, Some(&cfg.path))),
+        }
+    }
 }
 
 fn init_logging(quiet: bool) {
@@ -47,45 +194,334 @@ fn init_logging(quiet: bool) {
         .init();
 }
 
-fn main() {
-    let args = Arguments::from_args();
+// loads specs and creates state from their merged models and resources
+fn load_state(cfg: Config, inputs: &[PathBuf], no_defaults: bool) -> ram::Result<State> {
+    let mut specs = vec![];
+    for input in inputs {
+        let (spec, webhooks) = ram::spec::load_file_with_webhooks(input)?;
+        let specpath = input.parent().map(Path::to_path_buf).unwrap_or_default();
+        let grouping_strategy = cfg.grouping_strategy.unwrap_or(GroupingStrategy::FirstTag);
 
-    init_logging(args.quiet);
+        match spec {
+            openapi::OpenApi::V3_0(spec) => specs.push(SpecModels {
+                title: spec.info.title.clone(),
+                models: ram::generate_models_with_webhooks_v3(&spec, &webhooks, &specpath)?,
+                resource_groups: ram::generate_resources_v3(&spec, &specpath, grouping_strategy)?,
+                webhook_groups: ram::generate_webhooks_v3(
+                    &spec,
+                    &webhooks,
+                    &specpath,
+                    grouping_strategy,
+                )?,
+            }),
+            _ => return Err(Error::spec("/openapi", "unsupported openapi version")),
+        }
+    }
 
-    let cfg = Config::load_file(&args.config).unwrap();
-    let spec = openapi::from_path(&args.input).unwrap();
+    let (models, resource_groups, webhook_groups) =
+        ram::merge_specs(specs, cfg.conflicts.unwrap_or(ConflictPolicy::Error))?;
+    // field numbers are kept in lockfile, if configured
+    let field_lock = match cfg.get_lockfile() {
+        Some(path) => Some(FieldLock::load_file(&path)?),
+        None => None,
+    };
+    ram::create_state_with_webhooks(
+        cfg,
+        models,
+        resource_groups,
+        webhook_groups,
+        field_lock,
+        no_defaults,
+    )
+}
 
-    let mut specpath = args.input;
-    specpath.pop();
+// loads previous state either from state json or spec
+fn load_previous_state(input: &Input, previous: &Path) -> ram::Result<State> {
+    if previous.extension().and_then(|ext| ext.to_str()) == Some("json") {
+        let data = std::fs::read_to_string(previous).map_err(|e| Error::file(previous, e))?;
+        let value: serde_json::Value =
+            serde_json::from_str(&data).map_err(|e| Error::file(previous, e))?;
+        // state json is told apart from json spec by models
+        if value.get("models").is_some() {
+            return serde_json::from_value(value).map_err(|e| Error::file(previous, e));
+        }
+    }
+    load_state(
+        input.load_config()?,
+        &[previous.to_path_buf()],
+        input.no_defaults,
+    )
+}
 
-    // assemble state variable
-    let state = match spec {
-        openapi::OpenApi::V3_0(spec) => {
-            let models = ram::generate_models_v3(&spec, &specpath);
-            let resource_groups = ram::generate_resources_v3(
-                &spec,
-                &specpath,
-                cfg.grouping_strategy.unwrap_or(GroupingStrategy::FirstTag),
+fn run(args: Arguments) -> ram::Result<()> {
+    match args.command {
+        Command::Generate {
+            input,
+            output,
+            dry_run: true,
+            ..
+        } => {
+            let state = input.load_state()?;
+            let ignore = output.load_ignore(&state.cfg)?;
+            dry_run(&output.output, &ram::generate_files(state)?, &ignore)
+        }
+        Command::Generate {
+            input,
+            output,
+            manifest,
+            ..
+        } => {
+            generate(&output, manifest.as_deref(), input.load_state()?)?;
+            info!("All operations finished!");
+            Ok(())
+        }
+        Command::State { input } => {
+            let state = input.load_state()?;
+            println!(
+                "{}",
+                serde_json::to_string(&state).map_err(Error::serialize)?
             );
-            ram::create_state(cfg, models, resource_groups, args.no_defaults)
+            Ok(())
         }
-        _ => {
-            panic!("unsupported openapi version");
+        Command::Validate { input } => validate(&input),
+        Command::Langs => langs(),
+        Command::Init { lang, output } => init(&lang, &output),
+        Command::Eject {
+            lang,
+            output,
+            config,
+            force,
+        } => eject(
+            &lang,
+            &output.unwrap_or_else(|| PathBuf::from(&lang)),
+            &config,
+            force,
+        ),
+        Command::Check { input, output } => check(&output, input.load_state()?),
+        Command::Watch {
+            input,
+            output,
+            manifest,
+            interval,
+        } => watch(&input, &output, manifest.as_deref(), interval),
+        Command::Migrate {
+            input,
+            output,
+            from,
+            name,
+        } => {
+            let state = input.load_state()?;
+            let previous = load_previous_state(&input, &from)?;
+            let files = ram::generate_migration(&previous, &state, &name)?;
+            if files.is_empty() {
+                info!("No table changes, migration not generated");
+                return Ok(());
+            }
+            let ignore = output.load_ignore(&state.cfg)?;
+            ram::util::write_files_ignoring(&output.output, files, &ignore)?;
+            info!("All operations finished!");
+            Ok(())
         }
+    }
+}
+
+// writes generated files and manifest
+fn generate(output: &Output, manifest: Option<&Path>, state: State) -> ram::Result<()> {
+    let ignore = output.load_ignore(&state.cfg)?;
+    ram::write_lockfile(&state)?;
+    let (files, sources) = ram::generate_files_with_sources(state)?;
+    ram::util::write_files_ignoring(&output.output, files, &ignore)?;
+
+    // update manifest and remove files that are not generated anymore
+    if let Some(path) = manifest {
+        let previous = Manifest::load_file(path)?;
+        let current = Manifest::new(&output.output, &sources);
+        current.remove_stale(&previous, &output.output, &ignore)?;
+        current.write_file(path)?;
+    }
+    Ok(())
+}
+
+// regenerates on changes of watched files, until interrupted
+fn watch(
+    input: &Input,
+    output: &Output,
+    manifest: Option<&Path>,
+    interval: u64,
+) -> ram::Result<()> {
+    let mut files: Vec<PathBuf> = vec![];
+    let mut last: Option<ram::watch::Snapshot> = None;
+    loop {
+        // only modification times are polled between changes
+        if last.as_ref() != Some(&ram::watch::snapshot(&files)) {
+            if last.is_some() {
+                info!("Changes detected, regenerating...");
+            }
+            // watched files are collected again on changes, as references can change
+            files = watched_files(input);
+            last = Some(ram::watch::snapshot(&files));
+
+            // errors are reported, but watching continues
+            match input
+                .load_state()
+                .and_then(|state| generate(output, manifest, state))
+            {
+                Ok(_) => info!("Watching {} files for changes", files.len()),
+                Err(e) => error!("{}", e),
+            }
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(interval));
+    }
+}
+
+// returns config file and files generation depends on,
+// falls back to config and input files if they can't be read
+fn watched_files(input: &Input) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = input.config.iter().cloned().collect();
+    match input.load_config().and_then(|cfg| {
+        let inputs = input.inputs(&cfg)?;
+        ram::watch::watched_files(&cfg, &inputs)
+    }) {
+        Ok(watched) => files.extend(watched),
+        Err(e) => {
+            error!("{}", e);
+            files.extend(input.input.iter().cloned());
+        }
+    }
+    files
+}
+
+// reports problems of spec, config, lang and templates
+fn validate(input: &Input) -> ram::Result<()> {
+    let state = input.load_state()?;
+    let problems = ram::validate_files(&state);
+    for p in problems.iter() {
+        error!("{}", p);
+    }
+    match problems.len() {
+        0 => {
+            info!("Specs, config and templates are valid");
+            Ok(())
+        }
+        n => Err(Error::config(
+            "files",
+            format!("{} problems found in templates", n),
+        )),
+    }
+}
+
+// prints built-in langs with their files and default paths
+fn langs() -> ram::Result<()> {
+    for name in Assets::langs() {
+        let lang = Lang::load_file(Path::new(&name))?;
+        println!("{}", name);
+        for f in lang.files.iter() {
+            let source = f.emitter.as_ref().unwrap_or(&f.template);
+            let target = match (&f.filename, &f.file_in) {
+                (Some(filename), Some(inpath)) => format!("{}/{}", inpath, filename),
+                (Some(filename), None) => filename.clone(),
+                (None, Some(inpath)) => format!("{}/*", inpath),
+                (None, None) => "*".into(),
+            };
+            println!("  file: {} -> {}", source, target);
+        }
+        let mut paths: Vec<_> = lang.paths.iter().filter(|(_, p)| !p.is_empty()).collect();
+        paths.sort();
+        for (key, path) in paths {
+            println!("  path: {} = {}", key, path);
+        }
+    }
+    Ok(())
+}
+
+// writes config and template copies of a built-in lang, existing files are not overwritten
+fn init(lang: &str, output: &Path) -> ram::Result<()> {
+    let files = ram::init_files(lang)?;
+    if let Some((path, _)) = files.iter().find(|(path, _)| output.join(path).exists()) {
+        return Err(Error::file(output.join(path), "file already exists"));
+    }
+    ram::util::write_files_ignoring(output, files.into_iter().collect(), &Ignore::default())?;
+    info!(
+        "Created {}, generate with `ram generate -c {} -i <spec> -o <output>`",
+        output.join("config.yaml").display(),
+        output.join("config.yaml").display()
+    );
+    Ok(())
+}
+
+// ejects built-in lang and sets it as config lang, relative to config if possible
+fn eject(lang: &str, output: &Path, config: &Path, force: bool) -> ram::Result<()> {
+    let spec = Assets::eject(lang, output, force)?;
+    info!("Ejected {} lang to {}", lang, output.display());
+
+    let cfg_dir = config
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let cfg_dir = cfg_dir
+        .canonicalize()
+        .unwrap_or_else(|_| cfg_dir.to_path_buf());
+    let spec = spec.canonicalize().map_err(|e| Error::file(&spec, e))?;
+    let lang_path = spec.strip_prefix(&cfg_dir).unwrap_or(&spec);
+
+    let data = match config.exists() {
+        true => std::fs::read_to_string(config).map_err(|e| Error::file(config, e))?,
+        _ => String::new(),
     };
+    let data = ram::set_config_lang(&data, &lang_path.to_string_lossy());
+    std::fs::write(config, data).map_err(|e| Error::file(config, e))?;
+    info!(
+        "Set lang of {} to {}",
+        config.display(),
+        lang_path.display()
+    );
+    Ok(())
+}
 
-    // output raw state as json
-    if args.json {
-        println!(
-            "{}",
-            serde_json::to_string(&state).expect("failed to serialize state!")
-        );
+// prints changes that writing files would make
+fn dry_run(output: &Path, files: &HashMap<PathBuf, String>, ignore: &Ignore) -> ram::Result<()> {
+    for (path, change, bytes) in ram::util::plan_files(output, files, ignore)? {
+        let change = match change {
+            FileChange::Create => "create",
+            FileChange::Modify => "modify",
+            FileChange::Unchanged => "unchanged",
+        };
+        println!("{:<9} {} ({} bytes)", change, path.display(), bytes);
     }
+    Ok(())
+}
+
+// prints differences between generated files and output
+fn check(output: &Output, state: State) -> ram::Result<()> {
+    let ignore = output.load_ignore(&state.cfg)?;
+    let files = ram::generate_files(state)?;
+    let checks = ram::check_files(&output.output, &files, &ignore)?;
+    for c in checks.iter() {
+        match c {
+            FileCheck::Missing(path) => println!("missing: {}", path.display()),
+            FileCheck::Extra(path) => println!("extra: {}", path.display()),
+            FileCheck::Changed(_, diff) => print!("{}", diff),
+        }
+    }
+    if checks.is_empty() {
+        info!("Generated files are up to date");
+        Ok(())
+    } else {
+        Err(Error::file(
+            &output.output,
+            format!("{} files are not up to date", checks.len()),
+        ))
+    }
+}
+
+fn main() {
+    let args = Arguments::from_args();
+
+    init_logging(args.quiet);
 
-    // if output defined, write files
-    if let Some(output) = args.output {
-        let files = ram::generate_files(state);
-        ram::util::write_files(&output, files);
-        info!("All operations finished!")
+    if let Err(e) = run(args) {
+        error!("{}", e);
+        std::process::exit(1);
     }
 }
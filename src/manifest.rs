@@ -0,0 +1,102 @@
+use super::error::{Error, Result};
+use super::ignore::Ignore;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Generated files by path relative to output, with their template sources and content hashes.
+/// Previous manifest is used for removing generated files that are no longer generated.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub files: BTreeMap<PathBuf, ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    /// Template path or `emitter:<name>`
+    /// Template path, `emitter:<name>` or `lockfile`
+    pub template: String,
+    /// FNV-1a hash of file content as written
+    pub hash: String,
+}
+
+impl Manifest {
+    /// Creates manifest of files written under root, unwritten files are left out
+    pub fn new(root: &Path, sources: &HashMap<PathBuf, String>) -> Self {
+        let files = sources
+            .iter()
+            .filter_map(|(file, template)| {
+                let data = std::fs::read(root.join(file)).ok()?;
+                let entry = ManifestEntry {
+                    template: template.clone(),
+                    hash: hash(&data),
+                };
+                Some((file.clone(), entry))
+            })
+            .collect();
+        Manifest { files }
+    }
+
+    /// Reads manifest file, missing file results in an empty manifest
+    pub fn load_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = std::fs::read_to_string(path).map_err(|e| Error::file(path, e))?;
+        serde_json::from_str(&data).map_err(|e| Error::file(path, e))
+    }
+
+    pub fn write_file(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_string_pretty(self).map_err(|e| Error::file(path, e))?;
+        std::fs::write(path, data).map_err(|e| Error::file(path, e))
+    }
+
+    /// Removes files of previous manifest that are not in this one.
+    /// Files modified after generation and ignored files are kept.
+    /// Returns removed paths.
+    pub fn remove_stale(
+        &self,
+        previous: &Manifest,
+        root: &Path,
+        ignore: &Ignore,
+    ) -> Result<Vec<PathBuf>> {
+        let mut removed = vec![];
+        for (file, entry) in previous.files.iter() {
+            let path = root.join(file);
+            if self.files.contains_key(file) || !path.exists() || ignore.is_ignored(root, &path) {
+                continue;
+            }
+            let data = std::fs::read(&path).map_err(|e| Error::file(&path, e))?;
+            if hash(&data) != entry.hash {
+                warn!(
+                    "{} is not generated anymore, but it has been modified, keeping it",
+                    path.display()
+                );
+                continue;
+            }
+            info!("removing stale {}", path.display());
+            std::fs::remove_file(&path).map_err(|e| Error::file(&path, e))?;
+            removed.push(path);
+        }
+        Ok(removed)
+    }
+}
+
+/// Returns FNV-1a 64-bit hash of data as hex string
+pub fn hash(data: &[u8]) -> String {
+    let h = data.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, b| {
+        (h ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash(b""), "cbf29ce484222325");
+        assert_eq!(hash(b"a"), "af63dc4c8601ec8c");
+    }
+}
@@ -0,0 +1,364 @@
+use super::error::{Error, Result};
+use super::util;
+use super::{Model, ResourceGroup};
+use inflector::Inflector;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+
+// documentation doesn't make models or resources differ
+const DOC_KEYS: &[&str] = &["description", "summary", "example"];
+
+/// Handling of same named models that differ between specs
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Conflicting models are reported as an error
+    Error,
+    /// Conflicting models are prefixed with the pascal cased `info.title` of their spec
+    Prefix,
+}
+
+/// Models and resource groups generated from a single spec
+#[derive(Debug, Clone)]
+pub struct SpecModels {
+    /// Spec `info.title`
+    pub title: String,
+    pub models: Vec<Model>,
+    pub resource_groups: Vec<ResourceGroup>,
+    pub webhook_groups: Vec<ResourceGroup>,
+}
+
+/// Merges models and resource groups of several specs.
+/// Structurally equal models with the same name are kept once, conflicts are handled by policy.
+/// Resources of same named groups are merged into one group. Structurally equal resources
+/// with the same method and path are kept once, differing ones are reported as conflicts.
+/// Returns models, resource groups and webhook groups.
+pub fn merge_specs(
+    mut specs: Vec<SpecModels>,
+    policy: ConflictPolicy,
+) -> Result<(Vec<Model>, Vec<ResourceGroup>, Vec<ResourceGroup>)> {
+    let mut prefixed: HashSet<String> = HashSet::new();
+    loop {
+        let conflicts = conflicting_models(&specs);
+        if conflicts.is_empty() {
+            break;
+        }
+
+        if policy == ConflictPolicy::Error || conflicts.keys().any(|d| prefixed.contains(d)) {
+            return Err(Error::spec(
+                "/components/schemas",
+                format!("models differ between specs: {}", describe(&conflicts)),
+            ));
+        }
+
+        // renaming can make referencing models differ, so conflicts are checked again
+        for spec in specs.iter_mut() {
+            let prefix = spec.title.to_pascal_case();
+            for def in conflicts.keys() {
+                if spec.models.iter().any(|m| &m.def == def) {
+                    let renamed = format!("{}{}", prefix, def);
+                    rename_spec_model(spec, def, &renamed);
+                    prefixed.insert(renamed);
+                }
+            }
+        }
+    }
+
+    // paths can't be renamed, so differing resources are errors regardless of policy
+    let resources = conflicting_resources(&specs, |spec| &spec.resource_groups);
+    let webhooks = conflicting_resources(&specs, |spec| &spec.webhook_groups);
+    for (pointer, conflicts) in &[("/paths", resources), ("/webhooks", webhooks)] {
+        if !conflicts.is_empty() {
+            return Err(Error::spec(
+                pointer,
+                format!("operations differ between specs: {}", describe(conflicts)),
+            ));
+        }
+    }
+
+    let mut models: Vec<Model> = vec![];
+    let mut resource_groups: Vec<ResourceGroup> = vec![];
+    let mut webhook_groups: Vec<ResourceGroup> = vec![];
+    for spec in specs {
+        for model in spec.models {
+            if !models.iter().any(|m| m.def == model.def) {
+                models.push(model);
+            }
+        }
+        merge_groups(&mut resource_groups, spec.resource_groups);
+        merge_groups(&mut webhook_groups, spec.webhook_groups);
+    }
+    Ok((models, resource_groups, webhook_groups))
+}
+
+// adds groups, extending same named groups with their resources.
+// Resources already in groups are left out, as conflicts are checked before
+fn merge_groups(groups: &mut Vec<ResourceGroup>, other: Vec<ResourceGroup>) {
+    for mut group in other {
+        group.resources.retain(|r| {
+            !groups
+                .iter()
+                .flat_map(|g| g.resources.iter())
+                .any(|existing| existing.method == r.method && existing.path == r.path)
+        });
+        if group.resources.is_empty() {
+            continue;
+        }
+        group.has_body = group.resources.iter().any(|r| r.body.is_some());
+        match groups.iter_mut().find(|g| g.name == group.name) {
+            Some(existing) => {
+                existing.has_body |= group.has_body;
+                existing.resources.extend(group.resources);
+            }
+            None => groups.push(group),
+        }
+    }
+}
+
+// returns titles of specs defining each model that is not structurally equal in all of them
+fn conflicting_models(specs: &[SpecModels]) -> BTreeMap<String, Vec<String>> {
+    conflicts(specs.iter().flat_map(|spec| {
+        spec.models
+            .iter()
+            .map(move |m| (m.def.clone(), spec.title.as_str(), structure(m)))
+    }))
+}
+
+// returns titles of specs defining each `METHOD path` resource that is not structurally equal in all of them
+fn conflicting_resources<F>(specs: &[SpecModels], groups: F) -> BTreeMap<String, Vec<String>>
+where
+    F: Fn(&SpecModels) -> &Vec<ResourceGroup>,
+{
+    conflicts(specs.iter().flat_map(|spec| {
+        groups(spec)
+            .iter()
+            .flat_map(|g| g.resources.iter())
+            .map(move |r| {
+                let key = format!("{} {}", r.method, r.path);
+                (key, spec.title.as_str(), structure(r))
+            })
+    }))
+}
+
+// groups (key, spec title, structure) by key, returns titles of keys with differing structures
+fn conflicts<'a, I>(defined: I) -> BTreeMap<String, Vec<String>>
+where
+    I: Iterator<Item = (String, &'a str, Value)>,
+{
+    let mut by_key: BTreeMap<String, Vec<(&str, Value)>> = BTreeMap::new();
+    for (key, title, value) in defined {
+        by_key.entry(key).or_default().push((title, value));
+    }
+    by_key
+        .into_iter()
+        .filter(|(_, defs)| defs.iter().any(|(_, v)| *v != defs[0].1))
+        .map(|(key, defs)| {
+            let titles = defs.into_iter().map(|(t, _)| t.to_string()).collect();
+            (key, titles)
+        })
+        .collect()
+}
+
+// serializes value without documentation, for structural comparison
+fn structure<T: Serialize>(value: &T) -> Value {
+    fn strip_docs(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for key in DOC_KEYS {
+                    map.remove(*key);
+                }
+                map.values_mut().for_each(strip_docs);
+            }
+            Value::Array(items) => items.iter_mut().for_each(strip_docs),
+            _ => {}
+        }
+    }
+    let mut value = serde_json::to_value(value).unwrap_or_default();
+    strip_docs(&mut value);
+    value
+}
+
+fn describe(conflicts: &BTreeMap<String, Vec<String>>) -> String {
+    conflicts
+        .iter()
+        .map(|(key, titles)| format!("{} ({})", key, titles.join(", ")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// renames model and references to it in models and resources of spec
+fn rename_spec_model(spec: &mut SpecModels, from: &str, to: &str) {
+    for model in spec.models.iter_mut() {
+        if model.def == from {
+            model.name = to.to_string();
+        }
+        rename_references(model, from, to);
+    }
+    for resource in spec
+        .resource_groups
+        .iter_mut()
+        .chain(spec.webhook_groups.iter_mut())
+        .flat_map(|g| g.resources.iter_mut())
+    {
+        let params = resource
+            .path_params
+            .iter_mut()
+            .chain(resource.query_params.iter_mut())
+            .chain(resource.header_params.iter_mut())
+            .chain(resource.cookie_params.iter_mut());
+        for param in params {
+            rename_references(&mut param.model, from, to);
+        }
+        if let Some(ref mut body) = resource.body {
+            rename_references(&mut body.model, from, to);
+        }
+        let responses = resource
+            .responses
+            .values_mut()
+            .chain(resource.success_response.iter_mut())
+            .chain(resource.error_responses.iter_mut());
+        for response in responses {
+            let models = response
+                .model
+                .iter_mut()
+                .chain(response.content.iter_mut().flat_map(|c| c.model.iter_mut()));
+            for model in models {
+                rename_references(model, from, to);
+            }
+            for header in response.headers.iter_mut() {
+                rename_references(&mut header.model, from, to);
+            }
+        }
+    }
+}
+
+// renames def, ref path and references of model and its nested models
+fn rename_references(model: &mut Model, from: &str, to: &str) {
+    let rename_ref = |ref_path: &mut String| {
+        if util::model_name_from_ref(ref_path).as_deref() == Some(from) {
+            let idx = ref_path.rfind('/').unwrap_or(0);
+            ref_path.replace_range(idx + 1.., to);
+        }
+    };
+
+    if model.def == from {
+        model.def = to.to_string();
+    }
+    if let Some(ref mut ref_path) = model.ref_path {
+        rename_ref(ref_path);
+    }
+    if let Some(ref mut discriminator) = model.discriminator {
+        for target in discriminator.mapping.values_mut() {
+            if *target == from {
+                *target = to.to_string();
+            } else {
+                rename_ref(target);
+            }
+        }
+    }
+    for name in model.extends.iter_mut().chain(model.references.iter_mut()) {
+        if *name == from {
+            *name = to.to_string();
+        }
+    }
+
+    let children = model
+        .properties
+        .iter_mut()
+        .chain(model.items.iter_mut())
+        .chain(model.additional_properties.iter_mut())
+        .chain(model.variants.iter_mut())
+        .chain(model.object_properties.iter_mut())
+        .chain(model.array_properties.iter_mut())
+        .chain(model.primitive_properties.iter_mut());
+    for child in children {
+        rename_references(child, from, to);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(def: &str, ref_to: Option<&str>) -> Model {
+        let properties = ref_to
+            .map(|r| {
+                vec![Box::new(Model {
+                    def: r.into(),
+                    name: "child".into(),
+                    ref_path: Some(format!("#/components/schemas/{}", r)),
+                    ..Default::default()
+                })]
+            })
+            .unwrap_or_default();
+        Model {
+            def: def.into(),
+            name: def.into(),
+            references: ref_to.into_iter().map(String::from).collect(),
+            properties,
+            ..Default::default()
+        }
+    }
+
+    fn spec(title: &str, models: Vec<Model>) -> SpecModels {
+        SpecModels {
+            title: title.into(),
+            models,
+            resource_groups: vec![],
+            webhook_groups: vec![],
+        }
+    }
+
+    #[test]
+    fn test_merge_specs() {
+        let mut pet = model("Pet", None);
+        pet.schema_type = "differs".into();
+        let specs = vec![
+            spec(
+                "Pet Store",
+                vec![model("Pet", None), model("Owner", Some("Pet"))],
+            ),
+            spec(
+                "farm",
+                vec![pet, model("Owner", Some("Pet")), model("Cow", None)],
+            ),
+        ];
+
+        // equal models are merged
+        let (models, _, _) = merge_specs(specs[1..].to_vec(), ConflictPolicy::Error).unwrap();
+        assert_eq!(models.len(), 3);
+
+        let err = merge_specs(specs.clone(), ConflictPolicy::Error).unwrap_err();
+        assert!(err.to_string().contains("Pet (Pet Store, farm)"));
+
+        // referencing models are prefixed as well
+        let (models, _, _) = merge_specs(specs, ConflictPolicy::Prefix).unwrap();
+        let defs: Vec<&str> = models.iter().map(|m| m.def.as_str()).collect();
+        assert_eq!(
+            defs,
+            vec![
+                "PetStorePet",
+                "PetStoreOwner",
+                "FarmPet",
+                "FarmOwner",
+                "Cow"
+            ]
+        );
+        assert_eq!(models[3].references, vec!["FarmPet".to_string()]);
+        assert_eq!(
+            models[3].properties[0].ref_path.as_deref(),
+            Some("#/components/schemas/FarmPet")
+        );
+
+        // documentation doesn't make models differ
+        let mut pet = model("Pet", None);
+        pet.description = Some("differs".into());
+        let specs = vec![
+            spec("Pet Store", vec![model("Pet", None)]),
+            spec("farm", vec![pet]),
+        ];
+        let (models, _, _) = merge_specs(specs, ConflictPolicy::Error).unwrap();
+        assert_eq!(models.len(), 1);
+    }
+}
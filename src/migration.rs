@@ -0,0 +1,719 @@
+use super::error::{Error, Result};
+use super::helper::sql_string;
+use super::{Lang, Model};
+use inflector::Inflector;
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// SQL dialect of a lang, set with `dialect` in lang spec
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dialect {
+    Postgresql,
+    Mysql,
+    Sqlite,
+}
+
+impl Dialect {
+    pub fn from_lang(lang: &Lang) -> Result<Self> {
+        match lang.dialect.as_deref() {
+            Some("postgresql") => Ok(Dialect::Postgresql),
+            Some("mysql") => Ok(Dialect::Mysql),
+            Some("sqlite") => Ok(Dialect::Sqlite),
+            Some(other) => Err(Error::config(
+                "lang.dialect",
+                format!("unknown sql dialect '{}'", other),
+            )),
+            None => Err(Error::config(
+                "lang.dialect",
+                "lang has no sql dialect, migrations can't be generated",
+            )),
+        }
+    }
+}
+
+/// Up and down migration scripts, down reverts up in reverse order
+#[derive(Debug, Default, PartialEq)]
+pub struct Migration {
+    pub up: String,
+    pub down: String,
+    /// Changes the dialect can't express, left as comments in scripts.
+    /// Tables of these have to be rebuilt by hand.
+    pub unsupported: Vec<String>,
+}
+
+impl Migration {
+    pub fn is_empty(&self) -> bool {
+        self.up.is_empty()
+    }
+}
+
+// table built from a model with `x-sql-table`
+#[derive(Debug, Clone)]
+struct Table {
+    name: String,
+    pk: Option<String>,
+    /// Sequence of postgresql primary key, named after unescaped column
+    sequence: Option<String>,
+    columns: Vec<Column>,
+}
+
+impl Table {
+    // postgresql primary keys default to a sequence owned by the column, like in schema template
+    fn sequence(&self, column: &str) -> Option<&String> {
+        self.sequence
+            .as_ref()
+            .filter(|_| self.pk.as_deref() == Some(column))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Column {
+    name: String,
+    sql_type: String,
+    nullable: bool,
+    unique: bool,
+    /// Check constraint of enum columns in sqlite
+    check: Option<String>,
+    fk: Option<String>,
+    index: Option<String>,
+}
+
+// statements are ordered by phase in up migration, and reversed in down migration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Phase {
+    DropConstraints,
+    CreateTables,
+    AddColumns,
+    AlterColumns,
+    DropColumns,
+    DropTables,
+    AddConstraints,
+}
+
+struct Step {
+    phase: Phase,
+    up: String,
+    down: String,
+}
+
+/// Diffs `x-sql-table` models of previous and current (translated) models.
+/// Covers created and dropped tables, added, dropped and altered columns,
+/// foreign keys (`x-sql-fk`), unique constraints (`x-sql-unique`) and indexes (`x-sql-index`).
+pub fn diff(previous: &[Model], current: &[Model], lang: &Lang) -> Result<Migration> {
+    let dialect = Dialect::from_lang(lang)?;
+    let sql = Sql { dialect };
+    let (prev_tables, cur_tables) = (
+        tables(previous, lang, dialect),
+        tables(current, lang, dialect),
+    );
+
+    let mut steps: Vec<Step> = vec![];
+    let mut step = |phase: Phase, up: String, down: String| steps.push(Step { phase, up, down });
+
+    for prev in prev_tables.iter() {
+        match cur_tables.iter().find(|t| t.name == prev.name) {
+            None => step(
+                Phase::DropTables,
+                sql.drop_table(prev),
+                sql.create_table(prev),
+            ),
+            Some(cur) => {
+                for col in prev.columns.iter() {
+                    match cur.columns.iter().find(|c| c.name == col.name) {
+                        None => step(
+                            Phase::DropColumns,
+                            sql.drop_column(prev, col),
+                            sql.add_column(prev, col),
+                        ),
+                        Some(c)
+                            if c.sql_type != col.sql_type
+                                || c.nullable != col.nullable
+                                || c.check != col.check =>
+                        {
+                            step(
+                                Phase::AlterColumns,
+                                sql.alter_column(cur, col, c),
+                                sql.alter_column(prev, c, col),
+                            )
+                        }
+                        _ => {}
+                    }
+                }
+                for col in cur.columns.iter() {
+                    if !prev.columns.iter().any(|c| c.name == col.name) {
+                        step(
+                            Phase::AddColumns,
+                            sql.add_column(cur, col),
+                            sql.drop_column(cur, col),
+                        );
+                    }
+                }
+            }
+        }
+    }
+    for cur in cur_tables.iter() {
+        if !prev_tables.iter().any(|t| t.name == cur.name) {
+            step(
+                Phase::CreateTables,
+                sql.create_table(cur),
+                sql.drop_table(cur),
+            );
+        }
+    }
+
+    // foreign keys and indexes by table and column
+    let constraints = |tables: &[Table]| -> Vec<(String, Column)> {
+        tables
+            .iter()
+            .flat_map(|t| t.columns.iter().map(move |c| (t.name.clone(), c.clone())))
+            .collect()
+    };
+    let (prev_cols, cur_cols) = (constraints(&prev_tables), constraints(&cur_tables));
+    let find = |cols: &[(String, Column)], table: &str, column: &str| -> Option<Column> {
+        cols.iter()
+            .find(|(t, c)| t == table && c.name == column)
+            .map(|(_, c)| c.clone())
+    };
+
+    let mut keys: Vec<(String, String)> = vec![];
+    for (t, c) in prev_cols.iter().chain(cur_cols.iter()) {
+        if !keys.iter().any(|(kt, kc)| kt == t && *kc == c.name) {
+            keys.push((t.clone(), c.name.clone()));
+        }
+    }
+    for (table, column) in keys {
+        let (prev, cur) = (
+            find(&prev_cols, &table, &column),
+            find(&cur_cols, &table, &column),
+        );
+        let fk = |c: &Option<Column>| c.as_ref().and_then(|c| c.fk.clone());
+        let index = |c: &Option<Column>| c.as_ref().and_then(|c| c.index.clone());
+
+        // unique is a column constraint of created and dropped columns
+        if let (Some(prev), Some(cur)) = (&prev, &cur) {
+            if prev.unique && !cur.unique {
+                step(
+                    Phase::DropConstraints,
+                    sql.drop_unique(&table, &column),
+                    sql.add_unique(&table, &column),
+                );
+            }
+            if !prev.unique && cur.unique {
+                step(
+                    Phase::AddConstraints,
+                    sql.add_unique(&table, &column),
+                    sql.drop_unique(&table, &column),
+                );
+            }
+        }
+
+        // sqlite foreign keys are column constraints, so they're created and dropped with columns
+        let inline_fk = dialect == Dialect::Sqlite && (prev.is_none() || cur.is_none());
+        if fk(&prev) != fk(&cur) && !inline_fk {
+            if let Some(ref target) = fk(&prev) {
+                step(
+                    Phase::DropConstraints,
+                    sql.drop_fk(&table, &column),
+                    sql.add_fk(&table, &column, target),
+                );
+            }
+            if let Some(ref target) = fk(&cur) {
+                step(
+                    Phase::AddConstraints,
+                    sql.add_fk(&table, &column, target),
+                    sql.drop_fk(&table, &column),
+                );
+            }
+        }
+        if index(&prev) != index(&cur) {
+            if let Some(ref method) = index(&prev) {
+                step(
+                    Phase::DropConstraints,
+                    sql.drop_index(&table, &column),
+                    sql.create_index(&table, &column, method),
+                );
+            }
+            if let Some(ref method) = index(&cur) {
+                step(
+                    Phase::AddConstraints,
+                    sql.create_index(&table, &column, method),
+                    sql.drop_index(&table, &column),
+                );
+            }
+        }
+    }
+
+    steps.sort_by_key(|s| s.phase);
+    Ok(Migration {
+        up: steps.iter().map(|s| format!("{}\n", s.up)).collect(),
+        down: steps
+            .iter()
+            .rev()
+            .map(|s| format!("{}\n", s.down))
+            .collect(),
+        // statements the dialect can't express are comments
+        unsupported: steps
+            .iter()
+            .flat_map(|s| vec![&s.up, &s.down])
+            .filter_map(|statement| statement.strip_prefix("-- "))
+            .map(String::from)
+            .collect(),
+    })
+}
+
+/// Returns current UTC time as `YYYYMMDDhhmmss`, used as migration filename prefix
+pub fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86400, secs % 86400);
+
+    // civil date from days since epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+// collects tables from models with `x-sql-table`, column types are the ones of schema templates
+fn tables(models: &[Model], lang: &Lang, dialect: Dialect) -> Vec<Table> {
+    let ext = |m: &Model, key: &str| {
+        m.extensions
+            .get(key)
+            .and_then(Value::as_str)
+            .map(String::from)
+    };
+    // columns are snake cased and escaped like in schema templates
+    let column_name = |name: &str| {
+        lang.format("r", &name.to_snake_case())
+            .unwrap_or_else(|_| name.to_snake_case())
+    };
+    let values = |p: &Model| {
+        p.enum_values
+            .iter()
+            .map(|v| sql_string(&v.value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let sql_type = |p: &Model| match ext(p, "x-sql-type") {
+        Some(t) => t,
+        None if p.is_enum && dialect == Dialect::Mysql => format!("ENUM({})", values(p)),
+        None if p.is_enum && dialect == Dialect::Sqlite => "TEXT".into(),
+        None => p.schema_type.clone(),
+    };
+    models
+        .iter()
+        .filter_map(|m| {
+            let name = ext(m, "x-sql-table")?;
+            let pk = ext(m, "x-sql-pk");
+            Some(Table {
+                sequence: pk
+                    .as_ref()
+                    .filter(|_| dialect == Dialect::Postgresql)
+                    .map(|pk| format!("{}_{}_seq", name, pk.to_snake_case())),
+                pk: pk.map(|pk| column_name(&pk)),
+                name,
+                columns: m
+                    .primitive_properties
+                    .iter()
+                    .map(|p| Column {
+                        name: column_name(&p.name),
+                        sql_type: sql_type(p),
+                        nullable: p.nullable,
+                        unique: p
+                            .extensions
+                            .get("x-sql-unique")
+                            .and_then(Value::as_bool)
+                            .unwrap_or(false),
+                        check: if p.is_enum && dialect == Dialect::Sqlite {
+                            Some(format!("{} IN ({})", column_name(&p.name), values(p)))
+                        } else {
+                            None
+                        },
+                        fk: ext(p, "x-sql-fk"),
+                        index: ext(p, "x-sql-index"),
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+// statement formatting by dialect
+struct Sql {
+    dialect: Dialect,
+}
+
+impl Sql {
+    fn column_def(&self, table: &Table, col: &Column) -> String {
+        let is_pk = table.pk.as_ref() == Some(&col.name);
+        let mut def = format!("{} {}", col.name, col.sql_type);
+        match self.dialect {
+            Dialect::Mysql if is_pk => def.push_str(" AUTO_INCREMENT"),
+            Dialect::Sqlite if is_pk => def.push_str(" PRIMARY KEY AUTOINCREMENT"),
+            _ => {}
+        }
+        if !col.nullable && !(is_pk && self.dialect == Dialect::Sqlite) {
+            def.push_str(" NOT NULL");
+        }
+        if let Some(seq) = table.sequence(&col.name) {
+            def.push_str(&format!(" DEFAULT nextval('{}')", seq));
+        }
+        if col.unique {
+            def.push_str(" UNIQUE");
+        }
+        if let Some(ref check) = col.check {
+            def.push_str(&format!(" CHECK ({})", check));
+        }
+        if let (Dialect::Sqlite, Some(fk)) = (self.dialect, col.fk.as_ref()) {
+            def.push_str(&format!(" REFERENCES {} ON DELETE CASCADE", fk));
+        }
+        def
+    }
+
+    fn create_table(&self, table: &Table) -> String {
+        let mut defs: Vec<String> = table
+            .columns
+            .iter()
+            .map(|c| self.column_def(table, c))
+            .collect();
+        if let (Some(pk), false) = (table.pk.as_ref(), self.dialect == Dialect::Sqlite) {
+            defs.push(format!("PRIMARY KEY ({})", pk));
+        }
+        let create = format!(
+            "CREATE TABLE {} (\n    {}\n);",
+            table.name,
+            defs.join(",\n    ")
+        );
+        match table.pk {
+            Some(ref pk) => self.with_sequence(table, pk, create),
+            None => create,
+        }
+    }
+
+    // wraps statement creating column with creation and ownership of its sequence
+    fn with_sequence(&self, table: &Table, column: &str, statement: String) -> String {
+        match table.sequence(column) {
+            Some(seq) => format!(
+                "CREATE SEQUENCE {seq};\n{}\nALTER SEQUENCE {seq} OWNED BY {}.{};",
+                statement,
+                table.name,
+                column,
+                seq = seq
+            ),
+            None => statement,
+        }
+    }
+
+    fn drop_table(&self, table: &Table) -> String {
+        format!("DROP TABLE {};", table.name)
+    }
+
+    fn add_column(&self, table: &Table, col: &Column) -> String {
+        let add = format!(
+            "ALTER TABLE {} ADD COLUMN {};",
+            table.name,
+            self.column_def(table, col)
+        );
+        self.with_sequence(table, &col.name, add)
+    }
+
+    fn drop_column(&self, table: &Table, col: &Column) -> String {
+        format!("ALTER TABLE {} DROP COLUMN {};", table.name, col.name)
+    }
+
+    fn alter_column(&self, table: &Table, from: &Column, to: &Column) -> String {
+        match self.dialect {
+            Dialect::Postgresql => {
+                let mut statements = vec![];
+                if from.sql_type != to.sql_type {
+                    statements.push(format!(
+                        "ALTER TABLE {t} ALTER COLUMN {c} TYPE {ty} USING {c}::{ty};",
+                        t = table.name,
+                        c = to.name,
+                        ty = to.sql_type
+                    ));
+                }
+                if from.nullable != to.nullable {
+                    statements.push(format!(
+                        "ALTER TABLE {} ALTER COLUMN {} {} NOT NULL;",
+                        table.name,
+                        to.name,
+                        if to.nullable { "DROP" } else { "SET" }
+                    ));
+                }
+                statements.join("\n")
+            }
+            // unique is left out, as it would add another unique index
+            Dialect::Mysql => format!(
+                "ALTER TABLE {} MODIFY COLUMN {};",
+                table.name,
+                self.column_def(
+                    table,
+                    &Column {
+                        unique: false,
+                        ..to.clone()
+                    }
+                )
+            ),
+            Dialect::Sqlite => format!(
+                "-- sqlite can't alter column {}.{} ({} -> {}), the table has to be rebuilt",
+                table.name,
+                to.name,
+                self.column_def(table, from),
+                self.column_def(table, to)
+            ),
+        }
+    }
+
+    fn add_fk(&self, table: &str, column: &str, target: &str) -> String {
+        match self.dialect {
+            Dialect::Sqlite => format!(
+                "-- sqlite can't add foreign key {}.{} -> {}, the table has to be rebuilt",
+                table, column, target
+            ),
+            _ => format!(
+                "ALTER TABLE {t} ADD CONSTRAINT fk_{t}_{c} FOREIGN KEY ({c}) REFERENCES {target} ON DELETE CASCADE;",
+                t = table,
+                c = column,
+                target = target
+            ),
+        }
+    }
+
+    fn drop_fk(&self, table: &str, column: &str) -> String {
+        match self.dialect {
+            Dialect::Postgresql => format!(
+                "ALTER TABLE {t} DROP CONSTRAINT fk_{t}_{c};",
+                t = table,
+                c = column
+            ),
+            Dialect::Mysql => format!(
+                "ALTER TABLE {t} DROP FOREIGN KEY fk_{t}_{c};",
+                t = table,
+                c = column
+            ),
+            Dialect::Sqlite => format!(
+                "-- sqlite can't drop foreign key of {}.{}, the table has to be rebuilt",
+                table, column
+            ),
+        }
+    }
+
+    // constraints are named like the ones created by column definitions
+    fn add_unique(&self, table: &str, column: &str) -> String {
+        match self.dialect {
+            Dialect::Postgresql => format!(
+                "ALTER TABLE {t} ADD CONSTRAINT {t}_{c}_key UNIQUE ({c});",
+                t = table,
+                c = column
+            ),
+            Dialect::Mysql => format!(
+                "ALTER TABLE {t} ADD CONSTRAINT {c} UNIQUE ({c});",
+                t = table,
+                c = column
+            ),
+            Dialect::Sqlite => format!(
+                "-- sqlite can't add unique constraint of {}.{}, the table has to be rebuilt",
+                table, column
+            ),
+        }
+    }
+
+    fn drop_unique(&self, table: &str, column: &str) -> String {
+        match self.dialect {
+            Dialect::Postgresql => format!(
+                "ALTER TABLE {t} DROP CONSTRAINT {t}_{c}_key;",
+                t = table,
+                c = column
+            ),
+            Dialect::Mysql => format!("ALTER TABLE {} DROP INDEX {};", table, column),
+            Dialect::Sqlite => format!(
+                "-- sqlite can't drop unique constraint of {}.{}, the table has to be rebuilt",
+                table, column
+            ),
+        }
+    }
+
+    fn create_index(&self, table: &str, column: &str, method: &str) -> String {
+        let using = match self.dialect {
+            Dialect::Postgresql => format!(" USING {}", method),
+            Dialect::Mysql if method == "btree" || method == "hash" => {
+                format!(" USING {}", method.to_uppercase())
+            }
+            _ => String::new(),
+        };
+        match self.dialect {
+            Dialect::Postgresql => format!(
+                "CREATE INDEX idx_{t}_{c} ON {t}{using} ({c});",
+                t = table,
+                c = column,
+                using = using
+            ),
+            _ => format!(
+                "CREATE INDEX idx_{t}_{c} ON {t} ({c}){using};",
+                t = table,
+                c = column,
+                using = using
+            ),
+        }
+    }
+
+    fn drop_index(&self, table: &str, column: &str) -> String {
+        match self.dialect {
+            Dialect::Mysql => format!("DROP INDEX idx_{t}_{c} ON {t};", t = table, c = column),
+            _ => format!("DROP INDEX idx_{}_{};", table, column),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::EnumValue;
+    use serde_json::json;
+
+    fn table(columns: Vec<Model>) -> Model {
+        let mut model = Model {
+            name: "Dog".into(),
+            primitive_properties: columns.into_iter().map(Box::new).collect(),
+            ..Default::default()
+        };
+        model.extensions.insert("x-sql-table".into(), json!("dog"));
+        model.extensions.insert("x-sql-pk".into(), json!("dogId"));
+        model
+    }
+
+    fn column(name: &str, schema_type: &str) -> Model {
+        Model {
+            name: name.into(),
+            schema_type: schema_type.into(),
+            ..Default::default()
+        }
+    }
+
+    fn unique(mut column: Model) -> Model {
+        column.extensions.insert("x-sql-unique".into(), json!(true));
+        column
+    }
+
+    fn enumeration(name: &str, values: &[&str]) -> Model {
+        Model {
+            is_enum: true,
+            enum_values: values
+                .iter()
+                .map(|v| EnumValue {
+                    value: json!(v),
+                    variant: v.to_pascal_case(),
+                    number: None,
+                })
+                .collect(),
+            ..column(name, "text")
+        }
+    }
+
+    fn lang() -> Lang {
+        Lang::load_file(std::path::Path::new("postgresql")).unwrap()
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = vec![table(vec![column("id", "int"), column("name", "text")])];
+        let mut owner = column("ownerId", "int");
+        owner
+            .extensions
+            .insert("x-sql-fk".into(), json!("owner(id)"));
+        let current = vec![table(vec![column("id", "bigint"), owner])];
+
+        let migration = diff(&previous, &current, &lang()).unwrap();
+        assert_eq!(
+            migration.up,
+            "ALTER TABLE dog ADD COLUMN owner_id int NOT NULL;\n\
+             ALTER TABLE dog ALTER COLUMN id TYPE bigint USING id::bigint;\n\
+             ALTER TABLE dog DROP COLUMN name;\n\
+             ALTER TABLE dog ADD CONSTRAINT fk_dog_owner_id FOREIGN KEY (owner_id) REFERENCES owner(id) ON DELETE CASCADE;\n"
+        );
+        assert_eq!(
+            migration.down,
+            "ALTER TABLE dog DROP CONSTRAINT fk_dog_owner_id;\n\
+             ALTER TABLE dog ADD COLUMN name text NOT NULL;\n\
+             ALTER TABLE dog ALTER COLUMN id TYPE int USING id::int;\n\
+             ALTER TABLE dog DROP COLUMN owner_id;\n"
+        );
+
+        // no changes result in empty migration
+        assert!(diff(&current, &current, &lang()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_pk_and_unique() {
+        let previous = vec![table(vec![column("name", "text")])];
+        let current = vec![table(vec![
+            column("dogId", "int"),
+            unique(column("name", "text")),
+        ])];
+
+        let migration = diff(&previous, &current, &lang()).unwrap();
+        assert_eq!(
+            migration.up,
+            "CREATE SEQUENCE dog_dog_id_seq;\n\
+             ALTER TABLE dog ADD COLUMN dog_id int NOT NULL DEFAULT nextval('dog_dog_id_seq');\n\
+             ALTER SEQUENCE dog_dog_id_seq OWNED BY dog.dog_id;\n\
+             ALTER TABLE dog ADD CONSTRAINT dog_name_key UNIQUE (name);\n"
+        );
+        assert_eq!(
+            migration.down,
+            "ALTER TABLE dog DROP CONSTRAINT dog_name_key;\n\
+             ALTER TABLE dog DROP COLUMN dog_id;\n"
+        );
+        assert!(migration.unsupported.is_empty());
+
+        // sqlite can't alter constraints of existing columns
+        let sqlite = Lang::load_file(std::path::Path::new("sqlite")).unwrap();
+        let migration = diff(&previous, &current, &sqlite).unwrap();
+        assert_eq!(
+            migration.unsupported,
+            vec![
+                "sqlite can't add unique constraint of dog.name, the table has to be rebuilt",
+                "sqlite can't drop unique constraint of dog.name, the table has to be rebuilt",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_enum() {
+        let previous = vec![table(vec![column("name", "text")])];
+        let current = vec![table(vec![
+            column("name", "text"),
+            enumeration("size", &["small", "dog's"]),
+        ])];
+
+        // enum columns are typed like in schema templates
+        let mysql = Lang::load_file(std::path::Path::new("mysql")).unwrap();
+        assert_eq!(
+            diff(&previous, &current, &mysql).unwrap().up,
+            "ALTER TABLE dog ADD COLUMN size ENUM('small', 'dog''s') NOT NULL;\n"
+        );
+        let sqlite = Lang::load_file(std::path::Path::new("sqlite")).unwrap();
+        assert_eq!(
+            diff(&previous, &current, &sqlite).unwrap().up,
+            "ALTER TABLE dog ADD COLUMN size TEXT NOT NULL CHECK (size IN ('small', 'dog''s'));\n"
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use super::error::{Error, Result};
 use super::lang::Lang;
 use super::util;
 use indexmap::IndexMap;
+use log::warn;
 use openapi::v3_0::{ObjectOrReference, Schema};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -11,6 +13,18 @@ pub enum ModelType {
     Primitive,
     Object,
     Array,
+    Polymorphic,
+}
+
+/// Discriminator of a polymorphic (`oneOf` / `anyOf`) model
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
+pub struct Discriminator {
+    /// Name of the property which holds the variant tag
+    #[serde(alias = "propertyName")]
+    pub property_name: String,
+    /// Tag values mapped to schema refs or names
+    #[serde(default)]
+    pub mapping: IndexMap<String, String>,
 }
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
@@ -19,16 +33,46 @@ pub struct Model {
     pub name: String,
     #[serde(rename = "type")]
     pub schema_type: String,
+    /// Schema type as in spec, `type` is replaced by the lang type in translation
+    pub openapi_type: String,
     pub properties: Vec<Box<Model>>,
+    /// Names of `allOf` parent models, properties of which are merged in `properties`
+    pub extends: Vec<String>,
+    /// Set on properties merged from an `allOf` parent model
+    pub inherited: bool,
+    /// Name of the direct `allOf` parent model an inherited property is merged from
+    pub inherited_from: Option<String>,
     pub readonly: bool,
     pub additional_properties: Option<Box<Model>>,
     pub items: Option<Box<Model>>,
+    /// `oneOf` / `anyOf` variants of a polymorphic model
+    pub variants: Vec<Box<Model>>,
+    pub discriminator: Option<Discriminator>,
+    /// Tag value of a polymorphic model variant
+    pub discriminator_value: Option<String>,
+    /// Names of polymorphic models listing this model as a variant
+    pub implements: Vec<String>,
+    /// Set on properties holding the tag of a polymorphic model this model is a variant of
+    pub is_discriminator: bool,
+    /// Values listed in schema `enum`
+    pub enum_values: Vec<EnumValue>,
+    /// Validation constraints, None if schema doesn't define any
+    pub constraints: Option<Constraints>,
     pub description: Option<String>,
     pub format: Option<String>,
     pub nullable: bool,
+    /// Set on properties listed in parent schema `required`
+    pub required: bool,
     pub default: Option<Value>,
     #[serde(skip)]
     pub ref_path: Option<String>,
+    /// Names listed in `required` of the schema and its inline `allOf` members,
+    /// applied to properties merged from `allOf` parent models
+    #[serde(skip)]
+    pub required_properties: Vec<String>,
+    /// `oneOf` / `anyOf` keyword and index of a variant in its parent schema
+    #[serde(skip)]
+    pub variant_of: Option<(String, usize)>,
 
     /// Model extensions.
     /// Used for additional non-openapi specific information.
@@ -41,22 +85,166 @@ pub struct Model {
     pub is_object: bool,
     pub is_array: bool,
     pub is_primitive: bool,
+    pub is_polymorphic: bool,
+    pub is_enum: bool,
     pub has_date: bool,
     pub has_datetime: bool,
+    pub has_constraints: bool,
     pub object_properties: Vec<Box<Model>>,
     pub array_properties: Vec<Box<Model>>,
     pub primitive_properties: Vec<Box<Model>>,
+    /// Names of other models referenced by this model, for imports
+    pub references: Vec<String>,
+}
+
+/// Single value of an enum model
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EnumValue {
+    /// Raw value as in schema
+    pub value: Value,
+    /// Variant name, formatted with `enum_variant` lang helper
+    pub variant: String,
+    /// Stable value number, set for langs which number fields
+    pub number: Option<u64>,
+}
+
+/// Validation constraints of a model
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Constraints {
+    pub minimum: Option<Value>,
+    pub maximum: Option<Value>,
+    pub exclusive_minimum: bool,
+    pub exclusive_maximum: bool,
+    pub multiple_of: Option<Value>,
+    pub min_length: Option<Value>,
+    pub max_length: Option<Value>,
+    pub pattern: Option<String>,
+    pub min_items: Option<Value>,
+    pub max_items: Option<Value>,
+    pub unique_items: bool,
+
+    // additional helper properties, set if either of the bounds is present
+    pub has_range: bool,
+    pub has_length: bool,
+    pub has_items: bool,
+}
+
+impl Constraints {
+    // returns constraints from schema, or None if there isn't any
+    fn from_schema(schema: &Schema) -> Option<Self> {
+        fn number<T: Serialize>(v: &Option<T>) -> Option<Value> {
+            v.as_ref().and_then(|v| serde_json::to_value(v).ok())
+        }
+
+        let mut constraints = Constraints {
+            minimum: number(&schema.minimum),
+            maximum: number(&schema.maximum),
+            exclusive_minimum: schema.exclusive_minimum.unwrap_or(false),
+            exclusive_maximum: schema.exclusive_maximum.unwrap_or(false),
+            multiple_of: number(&schema.multiple_of),
+            min_length: number(&schema.min_length),
+            max_length: number(&schema.max_length),
+            pattern: schema.pattern.clone(),
+            min_items: number(&schema.min_items),
+            max_items: number(&schema.max_items),
+            unique_items: schema.unique_items.unwrap_or(false),
+            ..Default::default()
+        };
+
+        if constraints == Self::default() {
+            return None;
+        }
+
+        constraints.has_range = constraints.minimum.is_some() || constraints.maximum.is_some();
+        constraints.has_length =
+            constraints.min_length.is_some() || constraints.max_length.is_some();
+        constraints.has_items = constraints.min_items.is_some() || constraints.max_items.is_some();
+        Some(constraints)
+    }
+}
+
+impl Discriminator {
+    // returns tag value for variant model: key from mapping if listed there,
+    // otherwise the model name itself
+    fn tag_for(&self, def: &str, ref_path: Option<&String>) -> String {
+        self.mapping
+            .iter()
+            .find(|(_, target)| {
+                Some(*target) == ref_path
+                    || util::model_name_from_ref(target)
+                        .as_deref()
+                        .unwrap_or(target.as_str())
+                        == def
+            })
+            .map(|(tag, _)| tag.clone())
+            .unwrap_or_else(|| def.to_string())
+    }
+}
+
+// openapi type of a json value
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "string",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::Bool(_) => "boolean",
+        Value::Array(_) => "array",
+        _ => "object",
+    }
 }
 
 impl Model {
     pub fn new(name: &str, schema: &Schema, def: &str) -> Self {
-        let properties: Vec<Box<Model>> = schema
+        let schema_required: Vec<String> = schema.required.clone().unwrap_or_default();
+        let mut properties: Vec<Box<Model>> = schema
             .properties
             .iter()
             .flatten()
-            .map(|(name, schema)| Box::new(Model::new(&name, schema, "")))
+            .map(|(name, schema)| {
+                let mut property = Model::new(&name, schema, "");
+                property.required = schema_required.contains(name);
+                Box::new(property)
+            })
             .collect();
 
+        // allOf members: inline schemas are merged in place,
+        // referenced parent models are merged later in `inherit`
+        let mut parent_refs: Vec<String> = vec![];
+        let mut extends: Vec<String> = vec![];
+        let mut required_properties = schema_required;
+        for member in schema.all_of.iter().flatten() {
+            match member {
+                ObjectOrReference::Object(s) if s.ref_path.is_some() => {
+                    parent_refs.extend(s.ref_path.clone())
+                }
+                ObjectOrReference::Object(s) => {
+                    let inline = Model::new("", &s, "");
+                    extends.extend(inline.extends);
+                    properties.extend(inline.properties);
+                    required_properties.extend(inline.required_properties);
+                }
+                ObjectOrReference::Ref { ref_path } => parent_refs.push(ref_path.clone()),
+            }
+        }
+        // required lists of the schema and its members apply to all merged properties
+        for property in properties.iter_mut() {
+            property.required |= required_properties.contains(&property.name);
+        }
+
+        // `allOf: [$ref]` on a nested schema without properties of its own
+        // is only a wrapper (for adding description, nullable etc.), treat it as a plain reference
+        let ref_path = match schema.ref_path {
+            None if def == "" && properties.is_empty() && parent_refs.len() == 1 => {
+                parent_refs.pop()
+            }
+            _ => schema.ref_path.clone(),
+        };
+        extends.extend(
+            parent_refs
+                .iter()
+                .filter_map(|r| util::model_name_from_ref(r)),
+        );
+
         let additional_properties: Option<Box<Model>> = schema
             .additional_properties
             .as_ref()
@@ -65,23 +253,84 @@ impl Model {
                 _ => None,
             });
 
+        // discriminator is not part of the openapi schema struct,
+        // so it's picked up from the flattened extensions
+        let mut extensions = schema.extensions.clone();
+        let discriminator: Option<Discriminator> = extensions
+            .shift_remove("discriminator")
+            .and_then(|v| serde_json::from_value(v).ok());
+
+        // oneOf / anyOf variants, named after their ref or position,
+        // inline object variants are defined as models of their own
+        let variants: Vec<Box<Model>> = schema
+            .one_of
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(idx, s)| ("oneOf", idx, s))
+            .chain(
+                schema
+                    .any_of
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .map(|(idx, s)| ("anyOf", idx, s)),
+            )
+            .enumerate()
+            .map(|(position, (keyword, idx, s))| {
+                let s = util::obj_or_ref_schema(s);
+                let variant_name = util::extract_model_name(&s)
+                    .unwrap_or_else(|| format!("{}{}", name, position + 1));
+                let mut variant = Model::new(&variant_name, &s, "");
+                if variant.is_object && variant.ref_path.is_none() {
+                    variant.def = variant_name;
+                }
+                variant.discriminator_value = discriminator
+                    .as_ref()
+                    .map(|d| d.tag_for(&variant.def, variant.ref_path.as_ref()));
+                variant.variant_of = Some((keyword.into(), idx));
+                Box::new(variant)
+            })
+            .collect();
+
+        // enum values, variant names are formatted later in `translate`
+        let enum_values: Vec<EnumValue> = schema
+            .enum_values
+            .iter()
+            .flatten()
+            .map(|v| serde_json::to_value(v).unwrap_or_default())
+            .filter(|v| !v.is_null())
+            .map(|value| EnumValue {
+                variant: match value {
+                    Value::String(ref s) => s.clone(),
+                    ref v => format!("value_{}", v),
+                },
+                value,
+                number: None,
+            })
+            .collect();
+
+        // enums without type are typed by their values
         let schema_type = schema
             .schema_type
-            .as_ref()
-            .unwrap_or(&String::from("object"))
-            .to_owned();
+            .clone()
+            .or_else(|| enum_values.first().map(|ev| value_type(&ev.value).into()))
+            .unwrap_or_else(|| String::from("object"));
 
         // If input name is "", try to extract one from ref_path.
         // Otherwise use the name.
         let def: String = if def == "" {
-            util::extract_model_name(schema).unwrap_or_default()
+            ref_path
+                .as_ref()
+                .and_then(|r| util::model_name_from_ref(r))
+                .unwrap_or_default()
         } else {
             def.into()
         };
 
         let mut model = Model {
             name: name.into(),
-            ref_path: schema.ref_path.clone(),
+            ref_path,
             items: schema.items.as_ref().map(|s| {
                 let name = util::extract_model_name(s).unwrap_or_default();
                 Box::new(Model::new(&name, &s, ""))
@@ -90,12 +339,19 @@ impl Model {
             description: schema.description.clone(),
             format: schema.format.clone(),
             default: schema.default.clone(),
-            extensions: schema.extensions.clone(),
+            extensions,
             readonly: schema.read_only.unwrap_or(false),
             def,
+            openapi_type: schema_type.clone(),
             schema_type,
             properties,
+            extends,
             additional_properties,
+            variants,
+            discriminator,
+            enum_values,
+            constraints: Constraints::from_schema(schema),
+            required_properties,
             ..Default::default()
         };
 
@@ -106,9 +362,12 @@ impl Model {
         model
     }
 
-    fn apply_properties(&mut self) {
+    pub(crate) fn apply_properties(&mut self) {
         self.set_has_date();
         self.set_has_datetime();
+        self.set_has_constraints();
+        self.set_is_polymorphic();
+        self.set_is_enum();
         self.set_is_object();
         self.set_is_array();
         self.set_is_primitive();
@@ -121,6 +380,7 @@ impl Model {
         self.set_object_properties();
         self.set_array_properties();
         self.set_primitive_properties();
+        self.set_references();
     }
 
     // checks if any field contains format: date
@@ -145,8 +405,21 @@ impl Model {
         })
     }
 
+    // checks if any field has validation constraints
+    fn set_has_constraints(&mut self) {
+        self.has_constraints = self.properties.iter().any(|f| f.constraints.is_some())
+    }
+
+    fn set_is_polymorphic(&mut self) {
+        self.is_polymorphic = !self.variants.is_empty()
+    }
+
+    fn set_is_enum(&mut self) {
+        self.is_enum = !self.enum_values.is_empty()
+    }
+
     fn set_is_object(&mut self) {
-        self.is_object = self.schema_type == "object"
+        self.is_object = self.schema_type == "object" && !self.is_polymorphic
     }
 
     fn set_is_array(&mut self) {
@@ -154,7 +427,31 @@ impl Model {
     }
 
     fn set_is_primitive(&mut self) {
-        self.is_primitive = !self.is_array && !self.is_object
+        self.is_primitive = !self.is_array && !self.is_object && !self.is_polymorphic
+    }
+
+    // collects parent and referenced model names, including refs from nested inline schemas
+    fn set_references(&mut self) {
+        fn collect(model: &Model, refs: &mut Vec<String>) {
+            for child in model
+                .properties
+                .iter()
+                .chain(model.items.iter())
+                .chain(model.additional_properties.iter())
+                .chain(model.variants.iter())
+            {
+                if child.ref_path.is_none() {
+                    collect(child, refs);
+                } else if !refs.contains(&child.def) {
+                    refs.push(child.def.clone());
+                }
+            }
+        }
+
+        let mut refs = self.extends.clone();
+        collect(self, &mut refs);
+        refs.retain(|r| !r.is_empty() && *r != self.def);
+        self.references = refs;
     }
 
     fn set_primitive_properties<'a>(&'a mut self) {
@@ -207,31 +504,38 @@ impl Model {
             ModelType::Array
         } else if self.is_object {
             ModelType::Object
+        } else if self.is_polymorphic {
+            ModelType::Polymorphic
         } else {
             ModelType::Primitive
         }
     }
 
     // translates model
-    pub fn translate(self, lang: &Lang) -> Model {
+    pub fn translate(self, lang: &Lang) -> Result<Model> {
         let mut translated_type = match self.model_type() {
-            ModelType::Array => lang.translate_array(&self),
-            ModelType::Object => {
+            ModelType::Array => lang.translate_array(&self)?,
+            ModelType::Object | ModelType::Polymorphic => {
                 if let Some(ref refpath) = self.ref_path {
                     // this is a reference to another object
                     // get model name from ref_path
-                    util::model_name_from_ref(&refpath)
-                        .map(|t| lang.translate_modelname(&t))
-                        .expect("failed to get model name from ref")
+                    let t = util::model_name_from_ref(&refpath).unwrap_or_else(|| refpath.clone());
+                    lang.translate_modelname(&t)
                 } else {
                     // this is an inline object, which we name by it's key
                     lang.translate_modelname(&self.name)
                 }
             }
+            ModelType::Primitive if self.is_enum && self.ref_path.is_some() => {
+                // this is a reference to an enum model
+                let refpath = self.ref_path.clone().unwrap_or_default();
+                let t = util::model_name_from_ref(&refpath).unwrap_or(refpath);
+                lang.translate_modelname(&t)
+            }
             ModelType::Primitive => lang.translate_primitive(
                 &self.schema_type,
                 self.format.as_ref().unwrap_or(&String::from("default")),
-            ),
+            )?,
         };
 
         // format if nullable
@@ -241,75 +545,246 @@ impl Model {
                 .unwrap_or(translated_type)
         };
 
-        Model {
-            schema_type: translated_type,
-            properties: self
-                .properties
-                .into_iter()
-                .map(|m| Box::new(m.translate(lang)))
-                .collect(),
-            additional_properties: self
-                .additional_properties
-                .and_then(|m| Some(Box::new(m.translate(lang)))),
-            primitive_properties: self
-                .primitive_properties
+        let translate_properties = |properties: Vec<Box<Model>>| -> Result<Vec<Box<Model>>> {
+            properties
                 .into_iter()
-                .map(|m| Box::new(m.translate(lang)))
-                .collect(),
-            object_properties: self
-                .object_properties
+                .map(|m| m.translate_property(lang).map(Box::new))
+                .collect()
+        };
+
+        Ok(Model {
+            schema_type: translated_type,
+            properties: translate_properties(self.properties)?,
+            additional_properties: match self.additional_properties {
+                Some(m) if lang.flat_arrays && m.is_array => {
+                    return Err(Error::spec(
+                        "/additionalProperties",
+                        "arrays as map values are not supported by lang, wrap items in an object",
+                    ))
+                }
+                Some(m) => Some(Box::new(
+                    m.translate(lang)
+                        .map_err(|e| e.within(&["additionalProperties"]))?,
+                )),
+                None => None,
+            },
+            variants: self
+                .variants
                 .into_iter()
-                .map(|m| Box::new(m.translate(lang)))
-                .collect(),
-            array_properties: self
-                .array_properties
+                .map(|m| {
+                    let (keyword, idx) = m.variant_of.clone().unwrap_or_default();
+                    m.translate(lang)
+                        .map(Box::new)
+                        .map_err(|e| e.within(&[&keyword, &idx.to_string()]))
+                })
+                .collect::<Result<_>>()?,
+            enum_values: self
+                .enum_values
                 .into_iter()
-                .map(|m| Box::new(m.translate(lang)))
+                .map(|ev| EnumValue {
+                    variant: lang
+                        .format("enum_variant", &ev.variant)
+                        .unwrap_or(ev.variant),
+                    ..ev
+                })
                 .collect(),
+            primitive_properties: translate_properties(self.primitive_properties)?,
+            object_properties: translate_properties(self.object_properties)?,
+            array_properties: translate_properties(self.array_properties)?,
             ..self
+        })
+    }
+
+    // translates model as an object property,
+    // formatting non-required type with `optional` unless it's already nullable
+    fn translate_property(self, lang: &Lang) -> Result<Model> {
+        let optional = !self.required && !self.nullable;
+        let name = self.name.clone();
+        let mut model = self
+            .translate(lang)
+            .map_err(|e| e.within(&["properties", &name]))?;
+        if optional {
+            model.schema_type = lang
+                .format("optional", &model.schema_type)
+                .unwrap_or(model.schema_type)
+        }
+        Ok(model)
+    }
+
+    /// Returns inline object `oneOf` / `anyOf` variants as top-level models,
+    /// so that variant types are defined with other models
+    pub fn inline_variants(&self) -> Vec<Model> {
+        self.variants
+            .iter()
+            .filter(|v| v.is_object && v.ref_path.is_none())
+            .map(|v| Model {
+                variant_of: None,
+                ..(**v).clone()
+            })
+            .collect()
+    }
+
+    // merges properties from `allOf` parent models (from input map),
+    // properties defined on the model itself take precedence
+    pub fn inherit(mut self, models_map: &HashMap<String, Self>) -> Self {
+        let inherited = self.inherited_properties(models_map, &mut vec![self.def.clone()]);
+        if inherited.is_empty() {
+            return self;
+        }
+
+        let own = std::mem::take(&mut self.properties);
+        self.properties = inherited
+            .into_iter()
+            .filter(|p| !own.iter().any(|o| o.name == p.name))
+            .map(|mut p| {
+                p.inherited = true;
+                p.required |= self.required_properties.contains(&p.name);
+                p
+            })
+            .chain(own)
+            .collect();
+
+        // refresh derived helper properties
+        self.apply_properties();
+        self
+    }
+
+    // collects properties of parent models recursively, skipping already visited parents
+    fn inherited_properties(
+        &self,
+        models_map: &HashMap<String, Self>,
+        visited: &mut Vec<String>,
+    ) -> Vec<Box<Model>> {
+        let mut properties = vec![];
+        for parent in self.extends.iter() {
+            if visited.contains(parent) {
+                continue;
+            }
+            visited.push(parent.clone());
+            match models_map.get(parent) {
+                Some(m) => {
+                    let mut parent_properties = m.inherited_properties(models_map, visited);
+                    parent_properties.extend(m.properties.iter().cloned());
+                    properties.extend(parent_properties.into_iter().map(|mut p| {
+                        p.inherited_from = Some(parent.clone());
+                        p
+                    }));
+                }
+                None => warn!("allOf parent model '{}' not found", parent),
+            }
+        }
+        properties
+    }
+
+    // sets polymorphic models which list this model as a variant (from input map)
+    // and marks properties holding their tags
+    pub fn resolve_implements(mut self, models_map: &HashMap<String, Self>) -> Self {
+        if self.def.is_empty() {
+            return self;
+        }
+
+        let mut parents: Vec<&Model> = models_map
+            .values()
+            .filter(|m| m.variants.iter().any(|v| v.def == self.def))
+            .collect();
+        if parents.is_empty() {
+            return self;
+        }
+        parents.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for parent in parents {
+            self.implements.push(parent.name.clone());
+            if let Some(d) = parent.discriminator.as_ref() {
+                for property in self
+                    .properties
+                    .iter_mut()
+                    .filter(|p| p.name == d.property_name)
+                {
+                    property.is_discriminator = true;
+                }
+            }
+        }
+
+        // refresh derived helper properties
+        self.apply_properties();
+        self
+    }
+
+    // copies enum definitions (from input map) to properties referencing enum models,
+    // so that they are handled as primitives
+    pub fn resolve_enums(mut self, models_map: &HashMap<String, Self>) -> Self {
+        self.resolve_enum_refs(models_map);
+        // refresh derived helper properties
+        self.apply_properties();
+        self
+    }
+
+    fn resolve_enum_refs(&mut self, models_map: &HashMap<String, Self>) {
+        if let Some(target) = self
+            .ref_path
+            .as_ref()
+            .and_then(|refpath| util::model_name_from_ref(refpath))
+            .and_then(|name| models_map.get(&name))
+            .filter(|m| m.is_enum)
+        {
+            self.schema_type = target.schema_type.clone();
+            self.openapi_type = target.openapi_type.clone();
+            self.format = target.format.clone();
+            self.enum_values = target.enum_values.clone();
+        }
+
+        for child in self
+            .properties
+            .iter_mut()
+            .chain(self.items.iter_mut())
+            .chain(self.additional_properties.iter_mut())
+        {
+            child.resolve_enum_refs(models_map);
         }
     }
 
     // normalizes child refs (clones object from input map)
-    pub fn normalize(self, models_map: &HashMap<String, Self>) -> Self {
-        Self {
-            object_properties: self
-                .object_properties
-                .into_iter()
-                .map(|m| {
-                    models_map
-                        .get(&m.def)
-                        .expect(&format!("failed to get model '{}' from map", &m.def))
-                })
-                .cloned()
-                .map(Box::new)
-                .collect(),
+    pub fn normalize(self, models_map: &HashMap<String, Self>) -> Result<Self> {
+        let object_properties = self
+            .object_properties
+            .into_iter()
+            .map(|m| {
+                models_map
+                    .get(&m.def)
+                    .cloned()
+                    .map(Box::new)
+                    .ok_or_else(|| Error::spec(&m.def, "failed to get model from map"))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-            array_properties: self
-                .array_properties
-                .into_iter()
-                .map(|m| {
-                    let mut items: Box<Model> = m.items.expect("array item was None").clone();
-                    if items.is_object {
-                        items = Box::new(
-                            models_map
-                                .get(&items.def)
-                                .expect(&format!(
-                                    "failed to get array item '{}' from map",
-                                    &items.def
-                                ))
-                                .clone(),
-                        )
-                    }
-                    Model {
-                        items: Some(items),
-                        ..*m
-                    }
-                })
-                .map(Box::new)
-                .collect(),
+        let array_properties = self
+            .array_properties
+            .into_iter()
+            .map(|m| {
+                let mut items = m
+                    .items
+                    .clone()
+                    .ok_or_else(|| Error::spec(&m.def, "array items missing"))?;
+                if items.is_object {
+                    items = models_map
+                        .get(&items.def)
+                        .cloned()
+                        .map(Box::new)
+                        .ok_or_else(|| {
+                            Error::spec(&items.def, "failed to get array item from map")
+                        })?;
+                }
+                Ok(Box::new(Model {
+                    items: Some(items),
+                    ..*m
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
+        Ok(Self {
+            object_properties,
+            array_properties,
             ..self
-        }
+        })
     }
 }
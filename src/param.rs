@@ -13,10 +13,13 @@ pub struct Param {
 
 fn from_param(p: &Parameter) -> Option<Param> {
     p.schema.as_ref().and_then(|schema| {
+        let required = p.required.unwrap_or(false);
+        let mut model = Model::new(&p.name, &schema, "");
+        model.required = required;
         Some(Param {
             name: p.name.clone(),
-            model: Model::new(&p.name, &schema, ""),
-            required: p.required.unwrap_or(false),
+            model,
+            required,
         })
     })
 }
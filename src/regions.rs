@@ -0,0 +1,163 @@
+use super::error::{Error, Result};
+use std::path::Path;
+
+/// Marker starting a protected region, followed by region name.
+/// Markers can be inside any line comment, e.g. `// ram:keep-begin imports`
+pub const BEGIN_MARKER: &str = "ram:keep-begin";
+/// Marker ending a protected region
+pub const END_MARKER: &str = "ram:keep-end";
+
+// named region with its content lines, markers excluded
+struct Region<'a> {
+    name: &'a str,
+    content: Vec<&'a str>,
+}
+
+/// Splices protected region contents of existing file into rendered data.
+/// Returns rendered data as is if file does not exist.
+/// Fails if existing file has a non empty region which is not in rendered data,
+/// as its content would be lost.
+pub fn splice_existing(path: &Path, data: String) -> Result<String> {
+    if !path.exists() {
+        return Ok(data);
+    }
+    let existing = std::fs::read_to_string(path).map_err(|e| Error::file(path, e))?;
+    let (spliced, orphaned) = splice(&data, &existing).map_err(|e| Error::file(path, e))?;
+    if !orphaned.is_empty() {
+        return Err(Error::file(
+            path,
+            format!(
+                "protected regions {} are not in generated file, move or remove their content first",
+                orphaned
+                    .iter()
+                    .map(|name| format!("'{}'", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ));
+    }
+    Ok(spliced)
+}
+
+/// Replaces region contents of rendered with contents of same named regions in existing.
+/// Returns spliced data and names of existing non empty regions missing from rendered.
+/// Spliced data keeps line endings of rendered, `\r\n` or `\n`.
+pub fn splice(
+    rendered: &str,
+    existing: &str,
+) -> std::result::Result<(String, Vec<String>), String> {
+    let existing_regions = regions(existing)?;
+    let rendered_regions = regions(rendered)?;
+    if existing_regions.is_empty() {
+        return Ok((rendered.to_string(), vec![]));
+    }
+
+    let mut out: Vec<&str> = vec![];
+    let mut lines = rendered.lines();
+    while let Some(line) = lines.next() {
+        out.push(line);
+        let existing_region =
+            region_name(line).and_then(|name| existing_regions.iter().find(|r| r.name == name));
+        if let Some(region) = existing_region {
+            // skip rendered content, keeping end marker
+            for line in lines.by_ref() {
+                if line.contains(END_MARKER) {
+                    out.extend(region.content.iter());
+                    out.push(line);
+                    break;
+                }
+            }
+        }
+    }
+
+    let newline = if rendered.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut spliced = out.join(newline);
+    if rendered.ends_with('\n') {
+        spliced.push_str(newline);
+    }
+    // empty regions can be dropped, nothing is lost
+    let orphaned = existing_regions
+        .iter()
+        .filter(|r| !rendered_regions.iter().any(|rr| rr.name == r.name))
+        .filter(|r| r.content.iter().any(|line| !line.trim().is_empty()))
+        .map(|r| r.name.to_string())
+        .collect();
+    Ok((spliced, orphaned))
+}
+
+// returns region name if line begins a region
+fn region_name(line: &str) -> Option<&str> {
+    line.find(BEGIN_MARKER)
+        .and_then(|idx| line[idx + BEGIN_MARKER.len()..].split_whitespace().next())
+}
+
+fn regions(data: &str) -> std::result::Result<Vec<Region>, String> {
+    let mut regions = vec![];
+    let mut current: Option<Region> = None;
+    for (idx, line) in data.lines().enumerate() {
+        if let Some(name) = region_name(line) {
+            if let Some(r) = current {
+                return Err(format!(
+                    "line {}: region '{}' begins before region '{}' ends",
+                    idx + 1,
+                    name,
+                    r.name
+                ));
+            }
+            current = Some(Region {
+                name,
+                content: vec![],
+            });
+        } else if line.contains(END_MARKER) {
+            match current.take() {
+                Some(r) => regions.push(r),
+                None => return Err(format!("line {}: region end without begin", idx + 1)),
+            }
+        } else if let Some(r) = current.as_mut() {
+            r.content.push(line);
+        }
+    }
+    match current {
+        Some(r) => Err(format!("region '{}' is not ended", r.name)),
+        None => Ok(regions),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splice() {
+        let rendered = "struct A;\n// ram:keep-begin impl\n// ram:keep-end\n";
+        let existing = "struct Old;\n// ram:keep-begin impl\nimpl A {}\n// ram:keep-end\n\
+                        # ram:keep-begin gone\nx\n# ram:keep-end\n";
+        let (spliced, orphaned) = splice(rendered, existing).unwrap();
+        assert_eq!(
+            spliced,
+            "struct A;\n// ram:keep-begin impl\nimpl A {}\n// ram:keep-end\n"
+        );
+        assert_eq!(orphaned, vec!["gone".to_string()]);
+
+        assert!(splice(rendered, "// ram:keep-begin impl\n").is_err());
+
+        // empty regions are not orphaned
+        let existing = "// ram:keep-begin gone\n\n// ram:keep-end\n";
+        assert!(splice(rendered, existing).unwrap().1.is_empty());
+    }
+
+    #[test]
+    fn test_splice_crlf() {
+        let rendered = "struct A;\r\n// ram:keep-begin impl\r\n// ram:keep-end\r\n";
+        let existing = "// ram:keep-begin impl\r\nimpl A {}\r\n// ram:keep-end\r\n";
+        let (spliced, _) = splice(rendered, existing).unwrap();
+        assert_eq!(
+            spliced,
+            "struct A;\r\n// ram:keep-begin impl\r\nimpl A {}\r\n// ram:keep-end\r\n"
+        );
+    }
+}
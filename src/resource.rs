@@ -1,10 +1,11 @@
+use super::body::{get_body_operation, Body};
+use super::error::{Error, Result};
 use super::param::{get_params_operation, get_params_path, Param};
+use super::response::Response;
 use super::Lang;
-use super::Model;
 use indexmap::IndexMap;
 use itertools::Itertools;
-use openapi::v3_0::ObjectOrReference;
-use openapi::v3_0::{Operation, Parameter, PathItem};
+use openapi::v3_0::{Header, Operation, Parameter, PathItem, RequestBody};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -16,6 +17,9 @@ pub struct Resource {
     /// HTTP method
     pub method: String,
 
+    /// Set if resource is a webhook, `path` is then the webhook name
+    pub is_webhook: bool,
+
     /// Resource name usable for function names
     pub name: String,
 
@@ -31,78 +35,144 @@ pub struct Resource {
     /// Query params
     pub query_params: Vec<Param>,
 
-    /// Result
-    pub responses: HashMap<String, Model>,
+    /// Header params
+    pub header_params: Vec<Param>,
+
+    /// Cookie params
+    pub cookie_params: Vec<Param>,
+
+    /// Request body
+    pub body: Option<Body>,
+
+    /// Responses by status code
+    pub responses: IndexMap<String, Response>,
+
+    /// First 2xx response
+    pub success_response: Option<Response>,
+
+    /// 4xx, 5xx and default responses
+    pub error_responses: Vec<Response>,
 }
 
 impl Resource {
+    /// Creates resource of route operation, errors point within the operation
     pub fn new(
         path: &str,
         method: &str,
         op: &Operation,
         parameters: &HashMap<String, Parameter>,
-        mut path_params: Vec<Param>,
-        mut query_params: Vec<Param>,
-    ) -> Resource {
-        // extend route params with local method params
-        path_params.extend(get_params_operation(op, "path", parameters));
-        query_params.extend(get_params_operation(op, "query", parameters));
-
-        Resource {
+        request_bodies: &HashMap<String, RequestBody>,
+        headers: &HashMap<String, Header>,
+        path_item: &PathItem,
+    ) -> Result<Resource> {
+        // route params extended with local method params,
+        // which override route params with the same name
+        let params = |location: &str| -> Vec<Param> {
+            let local = get_params_operation(op, location, parameters);
+            get_params_path(path_item, location, parameters)
+                .into_iter()
+                .filter(|p| !local.iter().any(|l| l.name == p.name))
+                .chain(local)
+                .collect()
+        };
+
+        let name = op
+            .operation_id
+            .clone()
+            .ok_or_else(|| Error::spec("", "missing operationId"))?;
+
+        // all responses by status code
+        let responses: IndexMap<String, Response> = op
+            .responses
+            .iter()
+            .map(|(code, resp)| {
+                Response::new(code, resp, headers)
+                    .map(|r| (code.clone(), r))
+                    .map_err(|e| e.within(&["responses", code]))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Resource {
             path: path.into(),
             method: method.into(),
-            name: op
-                .operation_id
-                .as_ref()
-                .expect("missing operation_id on resource")
-                .clone(),
+            is_webhook: false,
+            name,
             summary: op.summary.clone(),
             description: op.description.clone(),
-            path_params,
-            query_params,
-            responses: op
-                // Take 200 application/json from response content and apply that as type
-                .responses
-                .iter()
-                .filter_map(|(code, resp)| {
-                    resp.content.as_ref().and_then(|contentmap| {
-                        contentmap.get("application/json").and_then(|mediatype| {
-                            match &mediatype.schema {
-                                Some(ObjectOrReference::Object(schema)) => {
-                                    Some((code.clone(), Model::new("", &schema, "")))
-                                }
-                                _ => None,
-                            }
-                        })
-                    })
-                })
-                .collect(),
-        }
+            path_params: params("path"),
+            query_params: params("query"),
+            header_params: params("header"),
+            cookie_params: params("cookie"),
+            body: get_body_operation(op, request_bodies),
+            success_response: responses.values().find(|r| r.is_success).cloned(),
+            error_responses: responses.values().filter(|r| r.is_error).cloned().collect(),
+            responses,
+        })
+    }
+
+    pub fn translate(self, lang: &Lang) -> Result<Resource> {
+        let path = self.path.clone();
+        let method = self.method.to_lowercase();
+        let root = if self.is_webhook { "webhooks" } else { "paths" };
+        self.translate_operation(lang)
+            .map_err(|e| e.within(&[root, &path, &method]))
     }
 
-    pub fn translate(self, lang: &Lang) -> Resource {
-        let tr_params = |params: Vec<Param>| {
+    fn translate_operation(self, lang: &Lang) -> Result<Resource> {
+        let tr_params = |params: Vec<Param>| -> Result<Vec<Param>> {
             params
                 .into_iter()
-                .map(|p| Param {
-                    model: p.model.translate(lang),
-                    ..p
+                .map(|p| -> Result<Param> {
+                    let name = p.name.clone();
+                    Ok(Param {
+                        model: p
+                            .model
+                            .translate(lang)
+                            .map_err(|e| e.within(&["parameters", &name, "schema"]))?,
+                        ..p
+                    })
                 })
                 .collect()
         };
+        let tr_response = |r: Response| -> Result<Response> {
+            let code = r.code.clone();
+            r.translate(lang)
+                .map_err(|e| e.within(&["responses", &code]))
+        };
 
-        Resource {
-            // also formats path
-            path: lang.format_path(self.path),
-            query_params: tr_params(self.query_params),
-            path_params: tr_params(self.path_params),
+        Ok(Resource {
+            // also formats path, webhook names are left as is
+            path: match self.is_webhook {
+                true => self.path,
+                _ => lang.format_path(self.path),
+            },
+            query_params: tr_params(self.query_params)?,
+            path_params: tr_params(self.path_params)?,
+            header_params: tr_params(self.header_params)?,
+            cookie_params: tr_params(self.cookie_params)?,
+            body: match self.body {
+                Some(b) => Some(Body {
+                    model: b
+                        .model
+                        .translate(lang)
+                        .map_err(|e| e.within(&["requestBody"]))?,
+                    ..b
+                }),
+                None => None,
+            },
             responses: self
                 .responses
                 .into_iter()
-                .map(|(key, model)| (key, model.translate(lang)))
-                .collect(),
+                .map(|(key, resp)| tr_response(resp).map(|r| (key, r)))
+                .collect::<Result<_>>()?,
+            success_response: self.success_response.map(tr_response).transpose()?,
+            error_responses: self
+                .error_responses
+                .into_iter()
+                .map(tr_response)
+                .collect::<Result<_>>()?,
             ..self
-        }
+        })
     }
 }
 
@@ -115,6 +185,8 @@ pub struct ResourceGroup {
     pub resources: Vec<Resource>,
     /// Grouping strategy used
     pub grouping_strategy: GroupingStrategy,
+    /// Set if any of the resources accepts a request body
+    pub has_body: bool,
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
@@ -125,131 +197,89 @@ pub enum GroupingStrategy {
     Operation,
 }
 
-/// Groups resources with given grouping strategy
+/// Returns defined operations of path item with their methods
+pub(crate) fn operations(item: &PathItem) -> Vec<(&'static str, &Operation)> {
+    vec![
+        ("GET", item.get.as_ref()),
+        ("PUT", item.put.as_ref()),
+        ("POST", item.post.as_ref()),
+        ("DELETE", item.delete.as_ref()),
+        ("OPTIONS", item.options.as_ref()),
+        ("HEAD", item.head.as_ref()),
+        ("PATCH", item.patch.as_ref()),
+        ("TRACE", item.trace.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(method, op)| op.map(|op| (method, op)))
+    .collect()
+}
+
+/// Groups resources with given grouping strategy.
+/// Paths are webhook names if `is_webhook` is set.
 pub fn group_resources(
     paths: &IndexMap<String, PathItem>,
     grouping_strategy: GroupingStrategy,
     parameters: &HashMap<String, Parameter>,
-) -> Vec<ResourceGroup> {
+    request_bodies: &HashMap<String, RequestBody>,
+    headers: &HashMap<String, Header>,
+    is_webhook: bool,
+) -> Result<Vec<ResourceGroup>> {
+    let root = if is_webhook { "webhooks" } else { "paths" };
     let iter = paths.iter().flat_map(|(path, item)| {
-        let path_params = get_params_path(item, "path", parameters);
-        let query_params = get_params_path(item, "query", parameters);
-        vec![
-            (
-                path.clone(),
-                "GET",
-                item.get.as_ref(),
-                path_params.clone(),
-                query_params.clone(),
-            ),
-            (
-                path.clone(),
-                "PUT",
-                item.put.as_ref(),
-                path_params.clone(),
-                query_params.clone(),
-            ),
-            (
-                path.clone(),
-                "POST",
-                item.post.as_ref(),
-                path_params.clone(),
-                query_params.clone(),
-            ),
-            (
-                path.clone(),
-                "DELETE",
-                item.delete.as_ref(),
-                path_params.clone(),
-                query_params.clone(),
-            ),
-            (
-                path.clone(),
-                "OPTIONS",
-                item.options.as_ref(),
-                path_params.clone(),
-                query_params.clone(),
-            ),
-            (
-                path.clone(),
-                "HEAD",
-                item.head.as_ref(),
-                path_params.clone(),
-                query_params.clone(),
-            ),
-            (
-                path.clone(),
-                "PATCH",
-                item.patch.as_ref(),
-                path_params.clone(),
-                query_params.clone(),
-            ),
-            (
-                path.clone(),
-                "TRACE",
-                item.trace.as_ref(),
-                path_params.clone(),
-                query_params.clone(),
-            ),
-        ]
-        .into_iter()
-        .filter_map(|(path, method, op, path_params, query_params)| {
-            op.and_then(|op| Some((path, method, op, path_params, query_params)))
-        })
+        operations(item)
+            .into_iter()
+            .map(move |(method, op)| (path.clone(), method, op, item))
     });
-    let strat_iter = iter.filter_map(|(path, method, op, path_params, query_params)| {
+    let strat_iter = iter.filter_map(|(path, method, op, item)| {
         match grouping_strategy {
             // everything is in same group
-            GroupingStrategy::Nothing => {
-                Some(("".into(), path, method, op, path_params, query_params))
-            }
+            GroupingStrategy::Nothing => Some(("".into(), path, method, op, item)),
 
             // groups by path
-            GroupingStrategy::Path => {
-                Some((path.clone(), path, method, op, path_params, query_params))
-            }
+            GroupingStrategy::Path => Some((path.clone(), path, method, op, item)),
 
             // groups by first tag
             GroupingStrategy::FirstTag => op
                 .tags
                 .as_ref()
                 .and_then(|tags| tags.get(0))
-                .and_then(|tag| Some((tag.clone(), path, method, op, path_params, query_params))),
+                .and_then(|tag| Some((tag.clone(), path, method, op, item))),
 
             // groups by operation id
-            GroupingStrategy::Operation => op.operation_id.as_ref().and_then(|operationid| {
-                Some((
-                    operationid.clone(),
-                    path,
-                    method,
-                    op,
-                    path_params,
-                    query_params,
-                ))
-            }),
+            GroupingStrategy::Operation => op
+                .operation_id
+                .as_ref()
+                .and_then(|operationid| Some((operationid.clone(), path, method, op, item))),
         }
     });
 
     // collect resourcegroups
     strat_iter
-        .group_by(|(key, _, _, _, _, _)| key.clone())
+        .group_by(|(key, _, _, _, _)| key.clone())
         .into_iter()
-        .map(|(key, group)| ResourceGroup {
-            name: key.into(),
-            resources: group
+        .map(|(key, group)| {
+            let resources: Vec<Resource> = group
                 .into_iter()
-                .map(|(_, path, method, op, path_params, query_params)| {
+                .map(|(_, path, method, op, item)| {
                     Resource::new(
                         path.as_str(),
                         method,
                         op,
                         parameters,
-                        path_params,
-                        query_params,
+                        request_bodies,
+                        headers,
+                        item,
                     )
+                    .map(|r| Resource { is_webhook, ..r })
+                    .map_err(|e| e.within(&[root, &path, &method.to_lowercase()]))
                 })
-                .collect(),
-            grouping_strategy,
+                .collect::<Result<_>>()?;
+            Ok(ResourceGroup {
+                name: key.into(),
+                has_body: resources.iter().any(|r| r.body.is_some()),
+                resources,
+                grouping_strategy,
+            })
         })
         .collect()
 }
@@ -0,0 +1,145 @@
+use super::error::{Error, Result};
+use super::param::Param;
+use super::util;
+use super::{Lang, Model};
+use openapi::v3_0::{Header, MediaType, ObjectOrReference, Response as ResponseSpec};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Response {
+    /// Status code or `default`
+    pub code: String,
+
+    /// Response description
+    pub description: Option<String>,
+
+    /// Content type, json is preferred if response has several
+    pub content_type: Option<String>,
+
+    /// Content model, None if response has no content schema
+    pub model: Option<Model>,
+
+    /// All content types with their models
+    pub content: Vec<Content>,
+
+    /// Response headers
+    pub headers: Vec<Param>,
+
+    // additional helper properties, derived from status code
+    pub is_success: bool,
+    pub is_error: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Content {
+    /// Media type
+    pub content_type: String,
+
+    /// Content model, None if media type has no schema
+    pub model: Option<Model>,
+}
+
+impl Content {
+    fn new(content_type: &str, mediatype: &MediaType) -> Self {
+        Content {
+            content_type: content_type.into(),
+            model: mediatype
+                .schema
+                .as_ref()
+                .map(util::obj_or_ref_schema)
+                .map(|schema| Model::new("", &schema, "")),
+        }
+    }
+}
+
+impl Response {
+    pub fn new(code: &str, resp: &ResponseSpec, headers: &HashMap<String, Header>) -> Result<Self> {
+        let preferred = util::preferred_content(resp.content.iter().flatten())
+            .map(|(content_type, mediatype)| Content::new(content_type, mediatype));
+
+        Ok(Response {
+            code: code.into(),
+            description: resp.description.clone(),
+            content_type: preferred.as_ref().map(|c| c.content_type.clone()),
+            model: preferred.and_then(|c| c.model),
+            content: resp
+                .content
+                .iter()
+                .flatten()
+                .map(|(content_type, mediatype)| Content::new(content_type, mediatype))
+                .collect(),
+            headers: resp
+                .headers
+                .iter()
+                .flatten()
+                .map(|(name, header)| -> Result<Param> {
+                    let h = match header {
+                        ObjectOrReference::Object(h) => h,
+                        ObjectOrReference::Ref { ref_path } => util::model_name_from_ref(ref_path)
+                            .and_then(|n| headers.get(&n))
+                            .ok_or_else(|| {
+                                Error::spec("", format!("unresolved reference {}", ref_path))
+                                    .within(&["headers", name])
+                            })?,
+                    };
+                    let required = h.required.unwrap_or(false);
+                    let mut model = Model::new(name, &h.schema.clone().unwrap_or_default(), "");
+                    model.required = required;
+                    Ok(Param {
+                        name: name.clone(),
+                        model,
+                        required,
+                    })
+                })
+                .collect::<Result<_>>()?,
+            is_success: code.starts_with('2'),
+            is_error: code.starts_with('4') || code.starts_with('5') || code == "default",
+        })
+    }
+
+    pub fn translate(self, lang: &Lang) -> Result<Response> {
+        let content_type = self.content_type.clone().unwrap_or_default();
+        Ok(Response {
+            model: self
+                .model
+                .map(|m| {
+                    m.translate(lang)
+                        .map_err(|e| e.within(&["content", &content_type, "schema"]))
+                })
+                .transpose()?,
+            content: self
+                .content
+                .into_iter()
+                .map(|c| -> Result<Content> {
+                    let content_type = c.content_type.clone();
+                    Ok(Content {
+                        model: c
+                            .model
+                            .map(|m| {
+                                m.translate(lang)
+                                    .map_err(|e| e.within(&["content", &content_type, "schema"]))
+                            })
+                            .transpose()?,
+                        ..c
+                    })
+                })
+                .collect::<Result<_>>()?,
+            headers: self
+                .headers
+                .into_iter()
+                .map(|p| -> Result<Param> {
+                    let name = p.name.clone();
+                    Ok(Param {
+                        model: p
+                            .model
+                            .translate(lang)
+                            .map_err(|e| e.within(&["headers", &name, "schema"]))?,
+                        ..p
+                    })
+                })
+                .collect::<Result<_>>()?,
+            ..self
+        })
+    }
+}
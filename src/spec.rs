@@ -0,0 +1,394 @@
+use super::error::{Error, Result};
+use super::swagger;
+use indexmap::IndexMap;
+use openapi::v3_0::PathItem;
+use openapi::OpenApi;
+use serde_json::{json, Value};
+use std::path::Path;
+
+// refs to these components are replaced with the referenced objects,
+// as they can't be expressed in the openapi structs
+const INLINED_COMPONENTS: &[&str] = &["#/components/responses/"];
+
+// limit for nested refs, guards against reference loops
+const MAX_REF_DEPTH: usize = 16;
+
+/// Webhook path items of openapi 3.1 spec by webhook name
+pub type Webhooks = IndexMap<String, PathItem>;
+
+/// Reads openapi spec from file
+pub fn load_file(path: &Path) -> Result<OpenApi> {
+    load_file_with_webhooks(path).map(|(spec, _)| spec)
+}
+
+/// Reads openapi spec and its webhooks from file, webhooks are empty for specs older than 3.1
+pub fn load_file_with_webhooks(path: &Path) -> Result<(OpenApi, Webhooks)> {
+    let ext: Option<&str> = path.extension().and_then(std::ffi::OsStr::to_str);
+    let data = std::fs::read_to_string(path).map_err(|e| Error::file(path, e))?;
+
+    let mut value: Value = match ext {
+        Some("json") => serde_json::from_str(&data).map_err(|e| Error::file(path, e))?,
+        _ => serde_yaml::from_str(&data).map_err(|e| Error::file(path, e))?,
+    };
+
+    // 3.1 and swagger 2.0 documents are converted to 3.0 shape
+    if is_v3_1(&value) {
+        convert_v3_1(&mut value)?;
+    } else if swagger::is_v2(&value) {
+        value = swagger::convert(value);
+    }
+
+    // inline component refs under paths and webhooks
+    let root = value.clone();
+    for key in &["paths", "webhooks"] {
+        if let Some(items) = value.get_mut(*key) {
+            inline_refs(items, &root, INLINED_COMPONENTS, 0);
+        }
+    }
+
+    // webhooks aren't part of 3.0 spec, items are read one by one to keep their order
+    let mut webhooks = Webhooks::new();
+    if let Some(Value::Object(items)) = value.as_object_mut().and_then(|v| v.remove("webhooks")) {
+        for (name, item) in items {
+            let item = serde_json::from_value(item).map_err(|e| Error::file(path, e))?;
+            webhooks.insert(name, item);
+        }
+    }
+
+    let spec = serde_json::from_value(value).map_err(|e| Error::file(path, e))?;
+    Ok((spec, webhooks))
+}
+
+fn is_v3_1(value: &Value) -> bool {
+    value
+        .get("openapi")
+        .and_then(Value::as_str)
+        .map(|v| v.starts_with("3.1"))
+        .unwrap_or(false)
+}
+
+// converts openapi 3.1 document to 3.0 equivalent:
+// schemas are converted with `convert_schema` and `$defs` are moved to components,
+// webhooks are left in place
+fn convert_v3_1(value: &mut Value) -> Result<()> {
+    let mut defs = vec![];
+    convert_schemas(value, &mut defs, false)?;
+
+    // paths is optional in 3.1
+    if value.get("paths").is_none() {
+        value["paths"] = json!({});
+    }
+
+    // hoisted definitions can't replace existing schemas or each other
+    for (name, schema) in defs {
+        let schemas = &mut value["components"]["schemas"];
+        match schemas.get(&name) {
+            None => schemas[name] = schema,
+            Some(existing) if *existing == schema => {}
+            Some(_) => {
+                return Err(
+                    Error::spec("", "$defs entry collides with existing schema").within(&[
+                        "components",
+                        "schemas",
+                        &name,
+                    ]),
+                )
+            }
+        }
+    }
+
+    value["openapi"] = json!("3.0.3");
+    Ok(())
+}
+
+// finds schema objects in document and converts them
+fn convert_schemas(
+    value: &mut Value,
+    defs: &mut Vec<(String, Value)>,
+    in_schemas: bool,
+) -> Result<()> {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                match key.as_str() {
+                    "schema" => convert_schema(v, defs),
+                    // components/schemas contains schemas by name
+                    "schemas" if in_schemas => match v.as_object_mut() {
+                        Some(schemas) => schemas.iter_mut().try_for_each(|(name, s)| {
+                            convert_schema(s, defs).map_err(|e| e.within(&[name]))
+                        }),
+                        None => Ok(()),
+                    },
+                    // examples are literal values
+                    "example" | "examples" => Ok(()),
+                    _ => convert_schemas(v, defs, key == "components"),
+                }
+                .map_err(|e| e.within(&[key]))?;
+            }
+            Ok(())
+        }
+        Value::Array(items) => items.iter_mut().enumerate().try_for_each(|(idx, v)| {
+            convert_schemas(v, defs, false).map_err(|e| e.within(&[&idx.to_string()]))
+        }),
+        _ => Ok(()),
+    }
+}
+
+// converts json schema 2020-12 keywords to openapi 3.0 schema
+fn convert_schema(schema: &mut Value, defs: &mut Vec<(String, Value)>) -> Result<()> {
+    let map = match schema.as_object_mut() {
+        Some(map) => map,
+        None => return Ok(()),
+    };
+
+    // type: [x, "null"] -> type: x, nullable: true
+    if let Some(Value::Array(types)) = map.get("type").cloned() {
+        let mut types: Vec<Value> = types;
+        let len = types.len();
+        types.retain(|t| t != "null");
+        if types.len() < len {
+            map.insert("nullable".into(), json!(true));
+        }
+        // multiple types can't be expressed in 3.0
+        if types.len() > 1 {
+            return Err(Error::spec(
+                "/type",
+                format!(
+                    "multiple types {} are not supported, use oneOf instead",
+                    Value::Array(types)
+                ),
+            ));
+        }
+        match types.into_iter().next() {
+            Some(t) => map.insert("type".into(), t),
+            None => map.remove("type"),
+        };
+    }
+
+    // oneOf/anyOf: [x, {type: "null"}] -> allOf: [x], nullable: true
+    for key in &["oneOf", "anyOf"] {
+        if let Some(Value::Array(members)) = map.get(*key).cloned() {
+            let (nulls, rest): (Vec<Value>, Vec<Value>) = members
+                .into_iter()
+                .partition(|m| m.get("type") == Some(&json!("null")));
+            if !nulls.is_empty() && rest.len() == 1 {
+                map.remove(*key);
+                map.insert("allOf".into(), Value::Array(rest));
+                map.insert("nullable".into(), json!(true));
+            }
+        }
+    }
+
+    // const -> single value enum
+    if let Some(c) = map.remove("const") {
+        map.entry("enum").or_insert_with(|| json!([c]));
+    }
+
+    // examples -> first example
+    if let Some(Value::Array(examples)) = map.remove("examples") {
+        if let Some(e) = examples.into_iter().next() {
+            map.entry("example").or_insert(e);
+        }
+    }
+
+    // numeric exclusive bounds -> boolean flags
+    for (exclusive, bound) in &[
+        ("exclusiveMinimum", "minimum"),
+        ("exclusiveMaximum", "maximum"),
+    ] {
+        if let Some(n) = map.get(*exclusive).filter(|v| v.is_number()).cloned() {
+            map.insert((*bound).into(), n);
+            map.insert((*exclusive).into(), json!(true));
+        }
+    }
+
+    // $defs are moved to components
+    if let Some(Value::Object(local_defs)) = map.remove("$defs") {
+        for (name, mut def) in local_defs {
+            convert_schema(&mut def, defs).map_err(|e| e.within(&["$defs", &name]))?;
+            defs.push((name, def));
+        }
+    }
+
+    // local refs to $defs point to components, refs to other files are kept
+    if let Some(r) = map.get("$ref").and_then(Value::as_str) {
+        if let Some(idx) = r.rfind("/$defs/").filter(|_| r.starts_with('#')) {
+            let name = r[idx + "/$defs/".len()..].to_string();
+            map.insert(
+                "$ref".into(),
+                json!(format!("#/components/schemas/{}", name)),
+            );
+        }
+    }
+
+    // nested schemas
+    for key in &["properties", "patternProperties"] {
+        if let Some(Value::Object(props)) = map.get_mut(*key) {
+            for (name, p) in props.iter_mut() {
+                convert_schema(p, defs).map_err(|e| e.within(&[key, name]))?;
+            }
+        }
+    }
+    for key in &["items", "additionalProperties", "not"] {
+        if let Some(s) = map.get_mut(*key) {
+            convert_schema(s, defs).map_err(|e| e.within(&[key]))?;
+        }
+    }
+    for key in &["allOf", "oneOf", "anyOf"] {
+        if let Some(Value::Array(members)) = map.get_mut(*key) {
+            for (idx, m) in members.iter_mut().enumerate() {
+                convert_schema(m, defs).map_err(|e| e.within(&[key, &idx.to_string()]))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// replaces local refs starting with any of the prefixes with the referenced object
+fn inline_refs(value: &mut Value, root: &Value, prefixes: &[&str], depth: usize) {
+    let target = value
+        .get("$ref")
+        .and_then(Value::as_str)
+        .filter(|r| prefixes.iter().any(|p| r.starts_with(p)))
+        .and_then(|r| root.pointer(&r[1..]))
+        .cloned();
+
+    if let Some(target) = target {
+        if depth < MAX_REF_DEPTH {
+            *value = target;
+            inline_refs(value, root, prefixes, depth + 1);
+        }
+        return;
+    }
+
+    match value {
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|v| inline_refs(v, root, prefixes, depth)),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|v| inline_refs(v, root, prefixes, depth)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_refs() {
+        let root = json!({
+            "components": {
+                "responses": {
+                    "NotFound": { "description": "not found" },
+                    "Missing": { "$ref": "#/components/responses/NotFound" }
+                }
+            }
+        });
+        let mut value = json!({
+            "404": { "$ref": "#/components/responses/Missing" },
+            "200": { "$ref": "#/components/schemas/Kept" }
+        });
+
+        inline_refs(&mut value, &root, INLINED_COMPONENTS, 0);
+
+        assert_eq!(value["404"], json!({ "description": "not found" }));
+        assert_eq!(value["200"], json!({ "$ref": "#/components/schemas/Kept" }));
+    }
+
+    #[test]
+    fn test_convert_v3_1() {
+        let mut value = json!({
+            "openapi": "3.1.0",
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": ["string", "null"], "examples": ["Rex"] },
+                            "kind": { "const": "pet" },
+                            "owner": { "oneOf": [{ "$ref": "#/$defs/Owner" }, { "type": "null" }] }
+                        },
+                        "$defs": {
+                            "Owner": { "type": "object" }
+                        }
+                    }
+                }
+            },
+            "webhooks": {
+                "newPet": { "post": { "operationId": "newPet" } }
+            }
+        });
+
+        convert_v3_1(&mut value).unwrap();
+
+        let props = &value["components"]["schemas"]["Pet"]["properties"];
+        assert_eq!(
+            props["name"],
+            json!({ "type": "string", "nullable": true, "example": "Rex" })
+        );
+        assert_eq!(props["kind"], json!({ "enum": ["pet"] }));
+        assert_eq!(
+            props["owner"],
+            json!({ "allOf": [{ "$ref": "#/components/schemas/Owner" }], "nullable": true })
+        );
+        assert_eq!(
+            value["components"]["schemas"]["Owner"],
+            json!({ "type": "object" })
+        );
+        assert_eq!(
+            value["webhooks"]["newPet"]["post"]["operationId"],
+            json!("newPet")
+        );
+        assert_eq!(value["openapi"], json!("3.0.3"));
+    }
+
+    #[test]
+    fn test_convert_v3_1_defs() {
+        let schemas = json!({
+            "Pet": {
+                "properties": {
+                    "owner": { "$ref": "#/$defs/Owner" },
+                    "vet": { "$ref": "vets.yaml#/$defs/Vet" }
+                },
+                "$defs": { "Owner": { "type": "object" } }
+            },
+            "Owner": { "type": "string" }
+        });
+        let mut value = json!({ "openapi": "3.1.0", "components": { "schemas": schemas } });
+
+        let err = convert_v3_1(&mut value).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "#/components/schemas/Owner: $defs entry collides with existing schema"
+        );
+
+        // refs to other files are kept
+        assert_eq!(
+            value["components"]["schemas"]["Pet"]["properties"]["vet"],
+            json!({ "$ref": "vets.yaml#/$defs/Vet" })
+        );
+    }
+
+    #[test]
+    fn test_convert_v3_1_multiple_types() {
+        let mut value = json!({
+            "openapi": "3.1.0",
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "properties": { "id": { "type": ["string", "integer", "null"] } }
+                    }
+                }
+            }
+        });
+
+        let err = convert_v3_1(&mut value).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "#/components/schemas/Pet/properties/id/type: \
+             multiple types [\"string\",\"integer\"] are not supported, use oneOf instead"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Config, Lang, Model, ResourceGroup};
+use super::{Config, FieldLock, Lang, Model, ResourceGroup};
 use serde::{Deserialize, Serialize};
 
 // full model generation state, to contain processed models and apis
@@ -8,5 +8,9 @@ pub struct State {
     pub cfg: Config,
     pub lang: Lang,
     pub resource_groups: Vec<ResourceGroup>,
+    pub webhook_groups: Vec<ResourceGroup>,
     pub no_defaults: bool,
+    /// Field numbers to write to `cfg.lockfile`
+    #[serde(skip)]
+    pub field_lock: Option<FieldLock>,
 }
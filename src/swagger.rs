@@ -0,0 +1,477 @@
+use serde_json::{json, Map, Value};
+
+// swagger 2.0 refs and their openapi 3.0 counterparts
+const REF_PREFIXES: &[(&str, &str)] = &[
+    ("#/definitions/", "#/components/schemas/"),
+    ("#/parameters/", "#/components/parameters/"),
+    ("#/responses/", "#/components/responses/"),
+];
+
+// parameter fields which stay in parameter, rest of the fields form the parameter schema
+const PARAM_FIELDS: &[&str] = &["name", "in", "description", "required", "allowEmptyValue"];
+
+const METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch"];
+
+const DEFAULT_MEDIA_TYPE: &str = "application/json";
+const FORM_MEDIA_TYPE: &str = "application/x-www-form-urlencoded";
+const MULTIPART_MEDIA_TYPE: &str = "multipart/form-data";
+
+pub fn is_v2(value: &Value) -> bool {
+    value
+        .get("swagger")
+        .and_then(Value::as_str)
+        .map(|v| v.starts_with("2"))
+        .unwrap_or(false)
+}
+
+/// Converts swagger 2.0 document to openapi 3.0 document
+pub fn convert(mut spec: Value) -> Value {
+    rewrite_refs(&mut spec);
+
+    let consumes = media_types(&spec, "consumes", &[]);
+    let produces = media_types(&spec, "produces", &[]);
+    let parameters = take_object(&mut spec, "parameters");
+
+    let mut components = Map::new();
+    components.insert(
+        "schemas".into(),
+        map_values(take_object(&mut spec, "definitions"), convert_schema),
+    );
+    // body and formData parameters are inlined to operations as request bodies
+    components.insert(
+        "parameters".into(),
+        Value::Object(
+            parameters
+                .iter()
+                .filter(|(_, p)| !is_body_param(p))
+                .map(|(name, p)| (name.clone(), convert_param(p.clone())))
+                .collect(),
+        ),
+    );
+    components.insert(
+        "responses".into(),
+        map_values(take_object(&mut spec, "responses"), |r| {
+            convert_response(r, &produces)
+        }),
+    );
+
+    let paths: Map<String, Value> = take_object(&mut spec, "paths")
+        .into_iter()
+        .map(|(path, item)| {
+            (
+                path,
+                convert_path_item(item, &parameters, &consumes, &produces),
+            )
+        })
+        .collect();
+
+    let mut out = json!({
+        "openapi": "3.0.3",
+        "info": spec["info"].take(),
+        "paths": paths,
+        "components": components,
+    });
+
+    if let Some(server) = server_url(&spec) {
+        out["servers"] = json!([{ "url": server }]);
+    }
+
+    for key in &["tags", "externalDocs"] {
+        if let Some(v) = take(&mut spec, key) {
+            out[*key] = v;
+        }
+    }
+
+    out
+}
+
+// rewrites refs to their openapi 3.0 locations
+fn rewrite_refs(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(r)) = map.get_mut("$ref") {
+                for (from, to) in REF_PREFIXES {
+                    if let Some(idx) = r.find(from) {
+                        r.replace_range(idx..idx + from.len(), to);
+                    }
+                }
+            }
+            map.values_mut().for_each(rewrite_refs);
+        }
+        Value::Array(items) => items.iter_mut().for_each(rewrite_refs),
+        _ => {}
+    }
+}
+
+// removes key from object value
+fn take(value: &mut Value, key: &str) -> Option<Value> {
+    value.as_object_mut().and_then(|map| map.remove(key))
+}
+
+fn take_object(value: &mut Value, key: &str) -> Map<String, Value> {
+    match take(value, key) {
+        Some(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+fn map_values(map: Map<String, Value>, f: impl Fn(Value) -> Value) -> Value {
+    Value::Object(map.into_iter().map(|(k, v)| (k, f(v))).collect())
+}
+
+// reads media types list from value or returns the defaults
+fn media_types(value: &Value, key: &str, defaults: &[String]) -> Vec<String> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|types| {
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect()
+        })
+        .filter(|types: &Vec<String>| !types.is_empty())
+        .unwrap_or_else(|| defaults.to_vec())
+}
+
+fn server_url(spec: &Value) -> Option<String> {
+    let host = spec.get("host").and_then(Value::as_str)?;
+    let scheme = spec
+        .get("schemes")
+        .and_then(|s| s.get(0))
+        .and_then(Value::as_str)
+        .unwrap_or("https");
+    let base_path = spec.get("basePath").and_then(Value::as_str).unwrap_or("");
+    Some(format!("{}://{}{}", scheme, host, base_path))
+}
+
+fn is_body_param(param: &Value) -> bool {
+    match param.get("in").and_then(Value::as_str) {
+        Some("body") | Some("formData") => true,
+        _ => false,
+    }
+}
+
+// converts swagger schema to openapi 3.0 schema
+fn convert_schema(mut schema: Value) -> Value {
+    let map = match schema.as_object_mut() {
+        Some(map) => map,
+        None => return schema,
+    };
+
+    if let Some(nullable) = map.remove("x-nullable") {
+        map.insert("nullable".into(), nullable);
+    }
+
+    if map.get("type").and_then(Value::as_str) == Some("file") {
+        map.insert("type".into(), json!("string"));
+        map.insert("format".into(), json!("binary"));
+    }
+
+    // discriminator is a plain property name
+    if let Some(Value::String(name)) = map.get("discriminator").cloned() {
+        map.insert("discriminator".into(), json!({ "propertyName": name }));
+    }
+
+    if let Some(Value::Object(props)) = map.get_mut("properties") {
+        for p in props.values_mut() {
+            *p = convert_schema(p.take());
+        }
+    }
+    for key in &["items", "additionalProperties"] {
+        if let Some(s) = map.get_mut(*key).filter(|s| s.is_object()) {
+            *s = convert_schema(s.take());
+        }
+    }
+    if let Some(Value::Array(members)) = map.get_mut("allOf") {
+        for m in members.iter_mut() {
+            *m = convert_schema(m.take());
+        }
+    }
+
+    schema
+}
+
+// splits non-body parameter to (parameter, schema)
+fn split_param(param: Value) -> (Map<String, Value>, Map<String, Value>) {
+    let mut fields = Map::new();
+    let mut schema = Map::new();
+    if let Value::Object(map) = param {
+        for (k, v) in map {
+            if PARAM_FIELDS.contains(&k.as_str()) || k.starts_with("x-") {
+                fields.insert(k, v);
+            } else if k != "collectionFormat" {
+                schema.insert(k, v);
+            }
+        }
+    }
+    (fields, schema)
+}
+
+// moves parameter type definitions to parameter schema
+fn convert_param(param: Value) -> Value {
+    if param.get("$ref").is_some() {
+        return param;
+    }
+    let (mut fields, schema) = split_param(param);
+    if !schema.is_empty() {
+        fields.insert("schema".into(), convert_schema(Value::Object(schema)));
+    }
+    Value::Object(fields)
+}
+
+fn convert_response(resp: Value, produces: &[String]) -> Value {
+    if resp.get("$ref").is_some() {
+        return resp;
+    }
+
+    let mut out = json!({
+        "description": resp.get("description").cloned().unwrap_or(json!("")),
+    });
+
+    if let Some(Value::Object(headers)) = resp.get("headers") {
+        out["headers"] = Value::Object(
+            headers
+                .iter()
+                .map(|(name, header)| {
+                    let (mut fields, schema) = split_param(header.clone());
+                    fields.insert("schema".into(), convert_schema(Value::Object(schema)));
+                    (name.clone(), Value::Object(fields))
+                })
+                .collect(),
+        );
+    }
+
+    if let Some(schema) = resp.get("schema") {
+        let schema = convert_schema(schema.clone());
+        out["content"] = Value::Object(
+            media_types_or_default(produces)
+                .into_iter()
+                .map(|ct| (ct, json!({ "schema": schema.clone() })))
+                .collect(),
+        );
+    }
+
+    out
+}
+
+fn media_types_or_default(types: &[String]) -> Vec<String> {
+    match types.is_empty() {
+        true => vec![DEFAULT_MEDIA_TYPE.into()],
+        _ => types.to_vec(),
+    }
+}
+
+fn convert_path_item(
+    mut item: Value,
+    parameters: &Map<String, Value>,
+    consumes: &[String],
+    produces: &[String],
+) -> Value {
+    // body params on path level are moved to operations
+    let (body_params, path_params): (Vec<Value>, Vec<Value>) = match take(&mut item, "parameters") {
+        Some(Value::Array(params)) => params
+            .into_iter()
+            .map(|p| resolve_param(p, parameters))
+            .partition(is_body_param),
+        _ => (vec![], vec![]),
+    };
+
+    if !path_params.is_empty() {
+        item["parameters"] = Value::Array(path_params.into_iter().map(convert_param).collect());
+    }
+
+    for method in METHODS {
+        if let Some(op) = item.get_mut(*method) {
+            *op = convert_operation(op.take(), &body_params, parameters, consumes, produces);
+        }
+    }
+
+    item
+}
+
+// inlines refs to body and formData parameters
+fn resolve_param(param: Value, parameters: &Map<String, Value>) -> Value {
+    param
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.rsplit('/').next())
+        .and_then(|name| parameters.get(name))
+        .filter(|p| is_body_param(p))
+        .cloned()
+        .unwrap_or(param)
+}
+
+fn convert_operation(
+    mut op: Value,
+    body_params: &[Value],
+    parameters: &Map<String, Value>,
+    consumes: &[String],
+    produces: &[String],
+) -> Value {
+    let consumes = media_types(&op, "consumes", consumes);
+    let produces = media_types(&op, "produces", produces);
+
+    let params: Vec<Value> = body_params
+        .iter()
+        .cloned()
+        .chain(match take(&mut op, "parameters") {
+            Some(Value::Array(params)) => params,
+            _ => vec![],
+        })
+        .map(|p| resolve_param(p, parameters))
+        .collect();
+
+    let (body, params): (Vec<Value>, Vec<Value>) = params.into_iter().partition(is_body_param);
+    let (form, body): (Vec<Value>, Vec<Value>) = body
+        .into_iter()
+        .partition(|p| p.get("in").and_then(Value::as_str) == Some("formData"));
+
+    let map = match op.as_object_mut() {
+        Some(map) => map,
+        None => return op,
+    };
+    map.remove("consumes");
+    map.remove("produces");
+    map.remove("schemes");
+
+    if !params.is_empty() {
+        map.insert(
+            "parameters".into(),
+            Value::Array(params.into_iter().map(convert_param).collect()),
+        );
+    }
+
+    if let Some(body) = body.into_iter().next() {
+        map.insert("requestBody".into(), convert_body(body, &consumes));
+    } else if !form.is_empty() {
+        map.insert("requestBody".into(), convert_form(form, &consumes));
+    }
+
+    if let Some(Value::Object(responses)) = map.get_mut("responses") {
+        for resp in responses.values_mut() {
+            *resp = convert_response(resp.take(), &produces);
+        }
+    }
+
+    op
+}
+
+fn convert_body(param: Value, consumes: &[String]) -> Value {
+    let schema = convert_schema(param.get("schema").cloned().unwrap_or(json!({})));
+    let content: Map<String, Value> = media_types_or_default(consumes)
+        .into_iter()
+        .map(|ct| (ct, json!({ "schema": schema.clone() })))
+        .collect();
+    let mut body = json!({
+        "required": param.get("required").cloned().unwrap_or(json!(false)),
+        "content": content,
+    });
+    if let Some(description) = param.get("description") {
+        body["description"] = description.clone();
+    }
+    body
+}
+
+// formData parameters are collected as properties of an object schema
+fn convert_form(params: Vec<Value>, consumes: &[String]) -> Value {
+    let has_file = params
+        .iter()
+        .any(|p| p.get("type").and_then(Value::as_str) == Some("file"));
+    let media_type = if has_file || consumes.iter().any(|c| c == MULTIPART_MEDIA_TYPE) {
+        MULTIPART_MEDIA_TYPE
+    } else {
+        FORM_MEDIA_TYPE
+    };
+
+    let mut properties = Map::new();
+    let mut required = vec![];
+    for p in params {
+        let (fields, mut schema) = split_param(p);
+        let name = match fields.get("name").and_then(Value::as_str) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        if let Some(description) = fields.get("description") {
+            schema.insert("description".into(), description.clone());
+        }
+        if fields.get("required") == Some(&json!(true)) {
+            required.push(json!(name));
+        }
+        properties.insert(name, convert_schema(Value::Object(schema)));
+    }
+
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+
+    json!({
+        "required": true,
+        "content": { media_type: { "schema": schema } },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let spec = json!({
+            "swagger": "2.0",
+            "info": { "title": "test", "version": "v1" },
+            "consumes": ["application/json"],
+            "paths": {
+                "/pets": {
+                    "post": {
+                        "operationId": "addPet",
+                        "parameters": [
+                            { "$ref": "#/parameters/PetBody" },
+                            { "name": "X-Request-Id", "in": "header", "type": "string" }
+                        ],
+                        "responses": {
+                            "200": {
+                                "description": "pet",
+                                "schema": { "$ref": "#/definitions/Pet" }
+                            }
+                        }
+                    }
+                }
+            },
+            "parameters": {
+                "PetBody": {
+                    "name": "pet",
+                    "in": "body",
+                    "required": true,
+                    "schema": { "$ref": "#/definitions/Pet" }
+                }
+            },
+            "definitions": {
+                "Pet": { "type": "object", "x-nullable": true }
+            }
+        });
+
+        let out = convert(spec);
+        let op = &out["paths"]["/pets"]["post"];
+
+        assert_eq!(out["openapi"], json!("3.0.3"));
+        assert_eq!(
+            out["components"]["schemas"]["Pet"],
+            json!({ "type": "object", "nullable": true })
+        );
+        assert_eq!(
+            op["parameters"],
+            json!([{ "name": "X-Request-Id", "in": "header", "schema": { "type": "string" } }])
+        );
+        assert_eq!(
+            op["requestBody"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/Pet" })
+        );
+        assert_eq!(
+            op["responses"]["200"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/Pet" })
+        );
+    }
+}
@@ -1,43 +1,85 @@
+use super::error::{Error, Result};
 use super::helper;
-use failure::Fallible;
-use glob::Pattern;
+use super::ignore::Ignore;
+use super::regions;
 use handlebars::Handlebars;
 use itertools::Itertools;
 use log::info;
-use openapi::v3_0::{ObjectOrReference, Parameter, Schema, Spec};
+use openapi::v3_0::{Header, MediaType, ObjectOrReference, Parameter, RequestBody, Schema, Spec};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-// returns ignore patterns from '.ramignore'
-pub fn ignore_patterns() -> Vec<Pattern> {
-    fs::read_to_string(".ramignore")
-        .and_then(|contents| {
-            Ok(contents
-                .split("\n")
-                .filter(|l| !l.is_empty())
-                .filter(|l| !l.starts_with("#"))
-                .filter_map(|line| Pattern::new(line).ok())
-                .collect())
-        })
-        .unwrap_or(vec![])
+// writes files in map, ignoring files by `.ramignore` of output root
+pub fn write_files(root: &Path, map: HashMap<PathBuf, String>) -> Result<()> {
+    write_files_ignoring(root, map, &Ignore::lookup(root, None))
 }
 
-// writes files in map
-pub fn write_files(root: &Path, map: HashMap<PathBuf, String>) {
-    let ignored = ignore_patterns();
-    for (file, data) in map.iter() {
+// writes files in map except ignored ones, keeping protected regions of existing files
+pub fn write_files_ignoring(
+    root: &Path,
+    map: HashMap<PathBuf, String>,
+    ignore: &Ignore,
+) -> Result<()> {
+    // all files are spliced before writing, so a failing one leaves the others untouched
+    let mut spliced = vec![];
+    for (file, data) in map.into_iter() {
         let path = root.join(&file);
-        if ignored.iter().any(|p| p.matches_path(&path)) {
-            info!("ignoring file {}", path.to_str().unwrap_or(""));
+        if ignore.is_ignored(root, &path) {
+            info!("ignoring file {}", path.display());
             continue;
         }
-        info!("writing {}", &path.to_str().unwrap());
+        let data = regions::splice_existing(&path, data)?;
+        spliced.push((path, data));
+    }
+    for (path, data) in spliced.into_iter() {
+        // unchanged files are not rewritten
+        if fs::read(&path)
+            .map(|d| d == data.as_bytes())
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        info!("writing {}", path.display());
         // create dirs if needed
-        fs::create_dir_all(path.parent().expect("failed to get parent dir"))
-            .expect("failed to create directory");
-        fs::write(path, data).expect(&format!("failed to write file {}", &file.display()));
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| Error::file(dir, e))?;
+        }
+        fs::write(&path, data).map_err(|e| Error::file(&path, e))?;
+    }
+    Ok(())
+}
+
+/// Change a write would make to a file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileChange {
+    Create,
+    Modify,
+    Unchanged,
+}
+
+// returns changes that `write_files` would make, with byte counts of written files
+pub fn plan_files(
+    root: &Path,
+    map: &HashMap<PathBuf, String>,
+    ignore: &Ignore,
+) -> Result<Vec<(PathBuf, FileChange, usize)>> {
+    let mut plan = vec![];
+    for (file, data) in map.iter() {
+        let path = root.join(&file);
+        if ignore.is_ignored(root, &path) {
+            continue;
+        }
+        let data = regions::splice_existing(&path, data.clone())?;
+        let change = match fs::read(&path) {
+            Err(_) => FileChange::Create,
+            Ok(ref existing) if existing == data.as_bytes() => FileChange::Unchanged,
+            _ => FileChange::Modify,
+        };
+        plan.push((path, change, data.len()));
     }
+    plan.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(plan)
 }
 
 pub fn extract_model_name(schema: &Schema) -> Option<String> {
@@ -47,6 +89,34 @@ pub fn extract_model_name(schema: &Schema) -> Option<String> {
         .and_then(|ref_path| model_name_from_ref(&ref_path))
 }
 
+// Returns schema from `ObjectOrReference`,
+// references are turned into schemas with only `ref_path` set
+pub fn obj_or_ref_schema(obj_or_ref: &ObjectOrReference<Schema>) -> Schema {
+    match obj_or_ref {
+        ObjectOrReference::Object(schema) => schema.clone(),
+        ObjectOrReference::Ref { ref_path } => Schema {
+            ref_path: Some(ref_path.clone()),
+            ..Default::default()
+        },
+    }
+}
+
+// Picks json content if available, otherwise the first listed content type
+pub fn preferred_content<'a, I>(content: I) -> Option<(&'a String, &'a MediaType)>
+where
+    I: Iterator<Item = (&'a String, &'a MediaType)> + Clone,
+{
+    content
+        .clone()
+        .find(|(content_type, _)| content_type.as_str() == "application/json")
+        .or_else(|| {
+            content
+                .clone()
+                .find(|(content_type, _)| content_type.ends_with("+json"))
+        })
+        .or_else(|| content.clone().next())
+}
+
 // Returns model name from ref path
 pub fn model_name_from_ref(ref_path: &str) -> Option<String> {
     if let Some(idx) = ref_path.rfind('/') {
@@ -64,7 +134,7 @@ pub fn init_handlebars(hb: &mut Handlebars) {
     helper::register_helpers(hb);
 }
 
-pub fn collect_schemas<'a>(spec: &'a Spec, root: &'a Path) -> Fallible<HashMap<String, Schema>> {
+pub fn collect_schemas<'a>(spec: &'a Spec, root: &'a Path) -> Result<HashMap<String, Schema>> {
     let component_schemas = spec
         .components
         .iter()
@@ -80,21 +150,51 @@ pub fn collect_schemas<'a>(spec: &'a Spec, root: &'a Path) -> Fallible<HashMap<S
         })
         .collect::<HashMap<String, Schema>>();
 
-    // collect and return schemas
-    Ok(iter_spec_schemas(spec)
+    // collect schemas from referenced files
+    let mut schemas = HashMap::new();
+    for path in iter_spec_schemas(spec)
         .flat_map(|schema| iter_ref_paths(&schema))
         .filter_map(ref_file)
         .unique()
-        .flat_map(move |path| schemas_from_ref(&root, path, &HashMap::new()))
-        .flatten()
-        .chain(component_schemas)
-        .collect())
+    {
+        schemas.extend(schemas_from_ref(&root, path, &HashMap::new())?);
+    }
+
+    // and return them with component schemas
+    schemas.extend(component_schemas);
+    Ok(schemas)
+}
+
+// returns files referenced by spec schemas, following references in referenced files
+pub fn collect_ref_files(spec: &Spec, root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = vec![];
+    let mut queue: Vec<PathBuf> = iter_spec_schemas(spec)
+        .flat_map(|schema| iter_ref_paths(&schema))
+        .filter_map(ref_file)
+        .map(|f| root.join(f))
+        .collect();
+    while let Some(path) = queue.pop() {
+        let path = path.canonicalize().unwrap_or(path);
+        if files.contains(&path) {
+            continue;
+        }
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        for schema in read_schemas(&path)?.values() {
+            queue.extend(
+                iter_ref_paths(schema)
+                    .filter_map(ref_file)
+                    .map(|f| dir.join(f)),
+            );
+        }
+        files.push(path);
+    }
+    Ok(files)
 }
 
 pub fn collect_parameters<'a>(
     spec: &'a Spec,
     _root: &'a Path,
-) -> Fallible<HashMap<String, Parameter>> {
+) -> Result<HashMap<String, Parameter>> {
     let component_parameters = spec
         .components
         .iter()
@@ -113,6 +213,47 @@ pub fn collect_parameters<'a>(
     Ok(component_parameters)
 }
 
+pub fn collect_request_bodies<'a>(
+    spec: &'a Spec,
+    _root: &'a Path,
+) -> Result<HashMap<String, RequestBody>> {
+    let component_request_bodies = spec
+        .components
+        .iter()
+        .flat_map(|components| {
+            components
+                .request_bodies
+                .iter()
+                .flatten()
+                .filter_map(|(k, v)| match v {
+                    ObjectOrReference::Object(t) => Some((k.clone(), t.clone())),
+                    _ => None,
+                })
+        })
+        .collect::<HashMap<String, RequestBody>>();
+
+    Ok(component_request_bodies)
+}
+
+pub fn collect_headers<'a>(spec: &'a Spec, _root: &'a Path) -> Result<HashMap<String, Header>> {
+    let component_headers = spec
+        .components
+        .iter()
+        .flat_map(|components| {
+            components
+                .headers
+                .iter()
+                .flatten()
+                .filter_map(|(k, v)| match v {
+                    ObjectOrReference::Object(t) => Some((k.clone(), t.clone())),
+                    _ => None,
+                })
+        })
+        .collect::<HashMap<String, Header>>();
+
+    Ok(component_headers)
+}
+
 // iterates all the schemas in Spec
 pub fn iter_spec_schemas<'a>(spec: &'a Spec) -> impl Iterator<Item = &'a Schema> {
     // helper function to map ObjectOrReference inner types
@@ -176,6 +317,20 @@ fn iter_ref_paths<'a>(schema: &'a Schema) -> Box<dyn Iterator<Item = &'a String>
                     .flat_map(|hashmap| hashmap.values().flat_map(|s| iter_ref_paths(&s))),
             )
             .chain(schema.items.iter().flat_map(|s| iter_ref_paths(&s)))
+            .chain(
+                schema
+                    .all_of
+                    .iter()
+                    .chain(schema.one_of.iter())
+                    .chain(schema.any_of.iter())
+                    .flatten()
+                    .flat_map(|obj_or_ref| match obj_or_ref {
+                        ObjectOrReference::Object(schema) => iter_ref_paths(&schema),
+                        ObjectOrReference::Ref { ref_path } => {
+                            Box::new(std::iter::once(ref_path)) as Box<dyn Iterator<Item = _>>
+                        }
+                    }),
+            )
             .chain(
                 schema
                     .additional_properties
@@ -200,15 +355,15 @@ pub fn join_relative(a: &Path, b: &Path) -> PathBuf {
 }
 
 // reads schemas from file
-fn read_schemas(path: &Path) -> Fallible<HashMap<String, Schema>> {
+fn read_schemas(path: &Path) -> Result<HashMap<String, Schema>> {
     let ext: Option<&str> = path.extension().and_then(std::ffi::OsStr::to_str);
-    let data = std::fs::read_to_string(path)?;
+    let data = std::fs::read_to_string(path).map_err(|e| Error::file(path, e))?;
 
-    Ok(match ext {
-        Some("yaml") | Some("yml") => serde_yaml::from_str(&data)?,
-        Some("json") => serde_json::from_str(&data)?,
-        _ => failure::bail!("unsupported file type"),
-    })
+    match ext {
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&data).map_err(|e| Error::file(path, e)),
+        Some("json") => serde_json::from_str(&data).map_err(|e| Error::file(path, e)),
+        _ => Err(Error::file(path, "unsupported file type")),
+    }
 }
 
 // creates file path,
@@ -224,13 +379,12 @@ fn schemas_from_ref(
     root: &Path,
     ref_path: &str,
     a: &HashMap<String, Schema>,
-) -> Fallible<HashMap<String, Schema>> {
+) -> Result<HashMap<String, Schema>> {
     let mut path: PathBuf = root.join(&ref_path);
 
     // read schemas from file to map b,
     // filtering out schemas that are already in map a
-    let b: HashMap<String, Schema> = read_schemas(&path)
-        .expect("failed to read schemas!")
+    let b: HashMap<String, Schema> = read_schemas(&path)?
         .into_iter()
         .filter(|(k, _)| !a.contains_key(k))
         .collect();
@@ -242,18 +396,19 @@ fn schemas_from_ref(
     // create next root path by popping filename from path
     path.pop();
 
-    Ok(
-        // fold values in map b with map c
-        // (which contains now all the schemas)
-        // recursively so we keep track of collected schemas so far
-        b.values()
-            .fold(merged, |mut acc: HashMap<String, Schema>, schema| {
-                for ref_path in iter_ref_paths(&schema).filter_map(ref_file).unique() {
-                    acc.extend(schemas_from_ref(&path, ref_path, &acc).unwrap());
-                }
-
-                acc
-            }),
+    // fold values in map b with map c
+    // (which contains now all the schemas)
+    // recursively so we keep track of collected schemas so far
+    b.values().try_fold(
+        merged,
+        |mut acc: HashMap<String, Schema>, schema| -> Result<HashMap<String, Schema>> {
+            for ref_path in iter_ref_paths(&schema).filter_map(ref_file).unique() {
+                let schemas = schemas_from_ref(&path, ref_path, &acc)?;
+                acc.extend(schemas);
+            }
+
+            Ok(acc)
+        },
     )
 }
 
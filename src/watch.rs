@@ -0,0 +1,46 @@
+use super::error::Result;
+use super::{spec, util, Config};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Modification times of watched files, missing files have none
+pub type Snapshot = Vec<(PathBuf, Option<SystemTime>)>;
+
+/// Returns files that generation depends on, besides config file: specs and their externally referenced
+/// schema files, lang spec and templates. Built-in lang spec and templates are not files, so they're left out.
+pub fn watched_files(cfg: &Config, inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let lang = cfg.get_lang()?;
+
+    let mut files = vec![];
+    for input in inputs {
+        let specpath = input.parent().map(Path::to_path_buf).unwrap_or_default();
+        files.push(input.clone());
+        if let openapi::OpenApi::V3_0(spec) = spec::load_file(input)? {
+            files.extend(util::collect_ref_files(&spec, &specpath)?);
+        }
+    }
+    files.extend(cfg.get_lang_file());
+    files.extend(
+        cfg.get_files(Some(&lang))
+            .into_iter()
+            .filter(|f| f.emitter.is_none())
+            .map(|f| PathBuf::from(f.template))
+            .filter(|path| path.is_file()),
+    );
+    // specs can share referenced schema files
+    let mut seen = HashSet::new();
+    files.retain(|f| seen.insert(f.clone()));
+    Ok(files)
+}
+
+/// Returns current modification times of files
+pub fn snapshot(files: &[PathBuf]) -> Snapshot {
+    files
+        .iter()
+        .map(|f| {
+            let modified = std::fs::metadata(f).and_then(|m| m.modified()).ok();
+            (f.clone(), modified)
+        })
+        .collect()
+}
@@ -6,13 +6,21 @@ use std::fs::DirEntry;
 use std::panic;
 use std::path::PathBuf;
 
+fn config(lang: &str) -> Config {
+    Config {
+        lang: lang.into(),
+        path: PathBuf::from("./tests"),
+        ..Default::default()
+    }
+}
+
 #[test]
 fn it_reads_models() {
     let spec = openapi::from_path("examples/openapi/petstore.yaml").unwrap();
     let specpath = PathBuf::from("examples/openapi/");
     match spec {
         openapi::OpenApi::V3_0(spec) => {
-            let models = ram::generate_models_v3(&spec, &specpath);
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
             assert_eq!(models.len(), 4);
         }
         _ => {}
@@ -21,14 +29,7 @@ fn it_reads_models() {
 
 #[test]
 fn it_generates_models_rust() {
-    let cfg = Config {
-        lang: String::from("rust"),
-        path: PathBuf::from("./tests"),
-        files: vec![],
-        helpers: HashMap::new(),
-        paths: HashMap::new(),
-        grouping_strategy: None,
-    };
+    let cfg = config("rust");
     let output = PathBuf::from("tests_output/models");
 
     let spec = openapi::from_path("examples/openapi/farm.yaml").unwrap();
@@ -40,10 +41,10 @@ fn it_generates_models_rust() {
 
     match spec {
         openapi::OpenApi::V3_0(spec) => {
-            models = ram::generate_models_v3(&spec, &specpath);
+            models = ram::generate_models_v3(&spec, &specpath).unwrap();
             assert_eq!(models.len(), models_count);
-            let state = ram::create_state(cfg, models.clone(), vec![], false);
-            ram::util::write_files(&output, ram::generate_files(state));
+            let state = ram::create_state(cfg, models.clone(), vec![], false).unwrap();
+            ram::util::write_files(&output, ram::generate_files(state).unwrap()).unwrap();
         }
         _ => {}
     };
@@ -87,6 +88,7 @@ fn it_generates_models_rust() {
             .clone()
             .filter(|p| {
                 p.nullable
+                    || !p.required
                     || (p.schema_type == "array"
                         && match p.items.as_ref() {
                             Some(item) => item.nullable,
@@ -168,10 +170,11 @@ fn it_generates_resources_rust() {
                 &spec,
                 &specpath,
                 cfg.grouping_strategy.unwrap_or(GroupingStrategy::FirstTag),
-            );
+            )
+            .unwrap();
             assert_eq!(resource_groups.len(), res_count);
-            let state = ram::create_state(cfg, vec![], resource_groups.clone(), false);
-            ram::util::write_files(&output, ram::generate_files(state));
+            let state = ram::create_state(cfg, vec![], resource_groups.clone(), false).unwrap();
+            ram::util::write_files(&output, ram::generate_files(state).unwrap()).unwrap();
         }
         _ => {}
     };
@@ -219,3 +222,1308 @@ fn it_generates_resources_rust() {
         // );
     }
 }
+
+#[test]
+fn it_merges_all_of() {
+    let spec = openapi::from_path("examples/openapi/allof.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            let dog = models.iter().find(|m| m.name == "Dog").unwrap();
+
+            assert_eq!(dog.extends, vec!["Animal".to_string()]);
+            // audit + animal + inline + own properties
+            assert_eq!(dog.properties.len(), 6);
+            assert_eq!(dog.properties.iter().filter(|p| p.inherited).count(), 4);
+            assert!(dog.has_datetime);
+
+            // required list applies to inherited and inline member properties
+            let required: Vec<&str> = dog
+                .properties
+                .iter()
+                .filter(|p| p.required)
+                .map(|p| p.name.as_str())
+                .collect();
+            assert_eq!(required, vec!["name", "barks"]);
+
+            // single ref allOf wrapper is a plain reference
+            let owner = dog.properties.iter().find(|p| p.name == "owner").unwrap();
+            assert_eq!(owner.def, "Owner");
+            assert!(owner.nullable);
+
+            // properties are tagged with the direct parent they come from
+            let cat = models.iter().find(|m| m.name == "Cat").unwrap();
+            assert_eq!(
+                cat.extends,
+                vec!["Animal".to_string(), "Tagged".to_string()]
+            );
+            let tag = cat.properties.iter().find(|p| p.name == "tag").unwrap();
+            assert_eq!(tag.inherited_from, Some("Tagged".to_string()));
+            let created = cat
+                .properties
+                .iter()
+                .find(|p| p.name == "createdAt")
+                .unwrap();
+            assert_eq!(created.inherited_from, Some("Animal".to_string()));
+
+            // java extends the first parent only, properties of others are declared inline
+            let state = ram::create_state(config("java"), models, vec![], false).unwrap();
+            let files = ram::generate_files(state).unwrap();
+            let (_, cat) = files.iter().find(|(p, _)| p.ends_with("Cat.java")).unwrap();
+            assert!(cat.contains("public class Cat extends Animal {"));
+            assert!(cat.contains("private String tag;"));
+            assert!(!cat.contains("private String name;"));
+        }
+        _ => {}
+    };
+}
+
+#[test]
+fn it_reads_polymorphic_models() {
+    let spec = openapi::from_path("examples/openapi/oneof.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            let pet = models.iter().find(|m| m.name == "Pet").unwrap();
+
+            assert!(pet.is_polymorphic);
+            assert!(!pet.is_object);
+            assert_eq!(pet.discriminator.as_ref().unwrap().property_name, "petType");
+
+            // tag comes from mapping or defaults to model name
+            let tags: Vec<Option<String>> = pet
+                .variants
+                .iter()
+                .map(|v| v.discriminator_value.clone())
+                .collect();
+            assert_eq!(tags, vec![Some("dog".into()), Some("Cat".into())]);
+
+            // inline object variants are defined as models named after their position
+            let contact = models.iter().find(|m| m.name == "Contact").unwrap();
+            let defs: Vec<&str> = contact.variants.iter().map(|v| v.def.as_str()).collect();
+            assert_eq!(defs, vec!["Contact1", "Contact2"]);
+            assert!(models.iter().any(|m| m.def == "Contact1" && m.is_object));
+            assert!(models.iter().any(|m| m.def == "Contact2" && m.is_object));
+
+            // go models have a single import block
+            let state = ram::create_state(config("go"), models, vec![], false).unwrap();
+            let files = ram::generate_files(state).unwrap();
+            let (_, pet) = files
+                .iter()
+                .find(|(p, _)| p.file_name().unwrap() == "pet.go")
+                .unwrap();
+            assert_eq!(pet.matches("import (").count(), 1);
+            assert!(pet.contains("func (Dog) isPet() {}"));
+        }
+        _ => {}
+    };
+}
+
+#[test]
+fn it_reads_enum_models() {
+    let cfg = config("rust");
+    let spec = openapi::from_path("examples/openapi/enum.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            let state = ram::create_state(cfg, models.clone(), vec![], false).unwrap();
+
+            let color = state.models.iter().find(|m| m.name == "Color").unwrap();
+            assert!(color.is_enum);
+            let variants: Vec<&str> = color
+                .enum_values
+                .iter()
+                .map(|v| v.variant.as_str())
+                .collect();
+            assert_eq!(variants, vec!["LightRed", "DarkBlue"]);
+
+            // reference to enum is a primitive typed by enum name
+            let paint = state.models.iter().find(|m| m.name == "Paint").unwrap();
+            let color_prop = paint.properties.iter().find(|p| p.name == "color").unwrap();
+            assert!(color_prop.is_enum && color_prop.is_primitive);
+            assert_eq!(color_prop.schema_type, "Option<Box<Color>>");
+
+            // integer enums are serialized by value
+            let files = ram::generate_files(state).unwrap();
+            let priority = files
+                .iter()
+                .find(|(path, _)| path.ends_with("priority.rs"))
+                .map(|(_, data)| data)
+                .unwrap();
+            assert!(priority.contains("#[repr(i64)]"));
+            assert!(priority.contains("    Value1 = 1,\n"));
+            assert!(!priority.contains("#[serde(rename"));
+
+            // enum without type is typed by its values
+            let size = files
+                .iter()
+                .find(|(path, _)| path.ends_with("size.rs"))
+                .map(|(_, data)| data)
+                .unwrap();
+            assert!(size.contains("#[serde(rename = \"it's large\")]"));
+
+            // other enums are rejected
+            let ratio = files
+                .iter()
+                .find(|(path, _)| path.ends_with("ratio.rs"))
+                .map(|(_, data)| data)
+                .unwrap();
+            assert!(ratio.contains("compile_error!"));
+            assert!(!ratio.contains("#[serde(rename"));
+
+            // sql enum literals are escaped
+            let state = ram::create_state(config("postgresql"), models, vec![], false).unwrap();
+            let files = ram::generate_files(state).unwrap();
+            let (_, schema) = files
+                .iter()
+                .find(|(path, _)| path.ends_with("schema.sql"))
+                .unwrap();
+            assert!(schema.contains("CREATE TYPE Size AS ENUM ('small', 'it''s large');"));
+            assert!(schema.contains("CREATE TYPE Priority AS ENUM ('1', '2', '3');"));
+        }
+        _ => {}
+    };
+}
+
+#[test]
+fn it_reads_constraints() {
+    let render = |lang: &str| match openapi::from_path("examples/openapi/constraints.yaml").unwrap()
+    {
+        openapi::OpenApi::V3_0(spec) => {
+            let models =
+                ram::generate_models_v3(&spec, &PathBuf::from("examples/openapi/")).unwrap();
+            let cfg = config(lang);
+            let state = ram::create_state(cfg, models, vec![], false).unwrap();
+            ram::generate_files(state).unwrap()
+        }
+        _ => panic!("unexpected spec version"),
+    };
+    let file = |files: &HashMap<PathBuf, String>, name: &str| {
+        files
+            .iter()
+            .find(|(path, _)| path.ends_with(name))
+            .map(|(_, data)| data.clone())
+            .unwrap()
+    };
+
+    match openapi::from_path("examples/openapi/constraints.yaml").unwrap() {
+        openapi::OpenApi::V3_0(spec) => {
+            let models =
+                ram::generate_models_v3(&spec, &PathBuf::from("examples/openapi/")).unwrap();
+            let pet = models.iter().find(|m| m.name == "Pet").unwrap();
+            assert!(pet.has_constraints);
+            let constraints = |name: &str| {
+                pet.properties
+                    .iter()
+                    .find(|p| p.name == name)
+                    .and_then(|p| p.constraints.clone())
+                    .unwrap()
+            };
+
+            let name = constraints("name");
+            assert!(name.has_length && !name.has_range);
+            assert_eq!(name.pattern.as_deref(), Some("^[a-z']+$"));
+            let age = constraints("age");
+            assert!(age.has_range && age.exclusive_maximum && !age.exclusive_minimum);
+            assert_eq!(age.minimum, Some(serde_json::json!(0)));
+            assert_eq!(constraints("legs").multiple_of, Some(serde_json::json!(2)));
+            let tags = constraints("tags");
+            assert!(tags.has_items && tags.unique_items);
+        }
+        _ => panic!("unexpected spec version"),
+    }
+
+    let rust = file(&render("rust"), "pet.rs");
+    assert!(rust.contains("#[validate(length(min = 1, max = 64))]"));
+    assert!(rust.contains("#[validate(range(min = 0, exclusive_max = 30))]"));
+    assert!(rust.contains("#[validate(regex = \"PET_NAME_PATTERN\")]"));
+    assert!(rust.contains("regex::Regex::new(r#\"^[a-z']+$\"#)"));
+    assert!(rust.contains("#[validate(custom = \"validate_pet_legs_multiple_of\")]"));
+    assert!(rust.contains("fn validate_pet_legs_multiple_of(value: &i64)"));
+
+    let go = file(&render("go"), "pet.go");
+    assert!(go.contains("validate:\"required,min=1,max=64\""));
+    assert!(go.contains("validate:\"omitempty,gte=0,lt=30\""));
+    assert!(go.contains("validate:\"omitempty,min=1,max=5,unique\""));
+
+    // quotes of patterns are escaped
+    let sql = file(&render("postgresql"), "schema.sql");
+    assert!(sql.contains("CHECK (\"name\" ~ '^[a-z'']+$')"));
+    assert!(sql.contains("CHECK (\"age\" < 30)"));
+    assert!(sql.contains("CHECK (\"legs\" % 2 = 0)"));
+}
+
+#[test]
+fn it_reads_header_and_cookie_params() {
+    let spec = openapi::from_path("examples/openapi/params.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let resource_groups =
+                ram::generate_resources_v3(&spec, &specpath, GroupingStrategy::FirstTag).unwrap();
+            let resource = &resource_groups[0].resources[0];
+            let names = |params: &Vec<ram::Param>| -> Vec<(String, bool)> {
+                params
+                    .iter()
+                    .map(|p| (p.name.clone(), p.required))
+                    .collect()
+            };
+
+            assert_eq!(names(&resource.path_params), vec![("id".into(), true)]);
+            // operation params override path params with the same name
+            assert_eq!(
+                names(&resource.header_params),
+                vec![("X-Request-Id".into(), false), ("If-Match".into(), true)]
+            );
+            assert_eq!(
+                names(&resource.cookie_params),
+                vec![("session".into(), true), ("theme".into(), false)]
+            );
+            assert!(resource.query_params.is_empty());
+        }
+        _ => {}
+    };
+}
+
+#[test]
+fn it_reads_responses() {
+    let spec = ram::spec::load_file(&PathBuf::from("examples/openapi/responses.yaml")).unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let resource_groups =
+                ram::generate_resources_v3(&spec, &specpath, GroupingStrategy::FirstTag).unwrap();
+            let resource = &resource_groups[0].resources[0];
+
+            assert_eq!(resource.responses.len(), 3);
+
+            // vendor json type is preferred over text
+            let success = resource.success_response.as_ref().unwrap();
+            assert_eq!(success.code, "200");
+            assert_eq!(
+                success.content_type.as_deref(),
+                Some("application/vnd.report+json")
+            );
+            assert_eq!(success.model.as_ref().unwrap().def, "Report");
+            let header = |name: &str| success.headers.iter().find(|h| h.name == name).unwrap();
+            assert!(header("X-Request-Id").required);
+
+            // components/headers ref is resolved
+            assert_eq!(header("X-Rate-Limit").model.schema_type, "integer");
+
+            // all content types are kept
+            assert_eq!(success.content.len(), 2);
+            let text = success
+                .content
+                .iter()
+                .find(|c| c.content_type == "text/plain")
+                .unwrap();
+            assert_eq!(text.model.as_ref().unwrap().schema_type, "string");
+
+            // components/responses ref is resolved
+            assert_eq!(resource.error_responses.len(), 2);
+            let not_found = resource.responses.get("404").unwrap();
+            assert_eq!(not_found.model.as_ref().unwrap().def, "Error");
+            assert!(resource.responses.get("default").unwrap().model.is_none());
+        }
+        _ => {}
+    };
+}
+
+#[test]
+fn it_reads_openapi_3_1() {
+    let cfg = config("rust");
+    let (spec, webhooks) =
+        ram::spec::load_file_with_webhooks(&PathBuf::from("examples/openapi/v3_1.yaml")).unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models =
+                ram::generate_models_with_webhooks_v3(&spec, &webhooks, &specpath).unwrap();
+            let resource_groups =
+                ram::generate_resources_v3(&spec, &specpath, GroupingStrategy::FirstTag).unwrap();
+            let webhook_groups =
+                ram::generate_webhooks_v3(&spec, &webhooks, &specpath, GroupingStrategy::FirstTag)
+                    .unwrap();
+
+            // $defs are hoisted to top-level models
+            assert!(models.iter().any(|m| m.name == "Owner"));
+
+            let pet = models.iter().find(|m| m.name == "Pet").unwrap();
+            let prop = |name: &str| pet.properties.iter().find(|p| p.name == name).unwrap();
+            assert!(prop("name").nullable);
+            assert_eq!(prop("kind").enum_values.len(), 1);
+            assert_eq!(prop("owner").def, "Owner");
+            assert!(prop("owner").nullable);
+
+            // webhooks are separated from routes
+            let state = ram::create_state_with_webhooks(
+                cfg,
+                models.clone(),
+                resource_groups,
+                webhook_groups,
+                None,
+                false,
+            )
+            .unwrap();
+            assert_eq!(state.resource_groups[0].resources.len(), 1);
+            let webhook = &state.webhook_groups[0].resources[0];
+            assert!(webhook.is_webhook);
+            assert_eq!(webhook.path, "newPet");
+            assert!(webhook.body.is_some());
+        }
+        _ => {}
+    };
+}
+
+#[test]
+fn it_reads_swagger_2_0() {
+    let spec = ram::spec::load_file(&PathBuf::from("examples/openapi/swagger.yaml")).unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            // Pet, Error and inline multipart body
+            assert_eq!(models.len(), 3);
+            assert!(models.iter().any(|m| m.def == "UploadPhotoBody"));
+            let pet = models.iter().find(|m| m.name == "Pet").unwrap();
+            assert!(
+                pet.properties
+                    .iter()
+                    .find(|p| p.name == "tag")
+                    .unwrap()
+                    .nullable
+            );
+
+            let resource_groups =
+                ram::generate_resources_v3(&spec, &specpath, GroupingStrategy::FirstTag).unwrap();
+            let resources = &resource_groups[0].resources;
+
+            // body parameter and path level header
+            let add_pet = resources.iter().find(|r| r.name == "addPet").unwrap();
+            let body = add_pet.body.as_ref().unwrap();
+            assert!(body.required);
+            assert_eq!(body.model.def, "Pet");
+            assert_eq!(add_pet.header_params[0].name, "X-Request-Id");
+            assert_eq!(
+                add_pet.success_response.as_ref().unwrap().headers[0].name,
+                "X-Rate-Limit"
+            );
+            assert_eq!(
+                add_pet.error_responses[0].model.as_ref().unwrap().def,
+                "Error"
+            );
+
+            // formData parameters are collected to a multipart body
+            let upload = resources.iter().find(|r| r.name == "uploadPhoto").unwrap();
+            let body = upload.body.as_ref().unwrap();
+            assert_eq!(body.content_type, "multipart/form-data");
+            assert_eq!(body.model.properties.len(), 2);
+            assert_eq!(body.model.name, "UploadPhotoBody");
+            assert_eq!(upload.path_params.len(), 1);
+        }
+        _ => {}
+    };
+}
+
+#[test]
+fn it_generates_typescript() {
+    let mut vars = HashMap::new();
+    vars.insert("client".to_string(), serde_json::json!(true));
+    let cfg = Config {
+        vars,
+        ..config("typescript")
+    };
+    let output = PathBuf::from("tests_output/typescript");
+
+    let spec = openapi::from_path("examples/openapi/farm.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            let resource_groups =
+                ram::generate_resources_v3(&spec, &specpath, GroupingStrategy::FirstTag).unwrap();
+            let models_count = models.len();
+            let state = ram::create_state(cfg, models, resource_groups, false).unwrap();
+            ram::util::write_files(&output, ram::generate_files(state).unwrap()).unwrap();
+
+            // models + index barrel
+            let files = std::fs::read_dir("tests_output/typescript/src/model").unwrap();
+            assert_eq!(files.count(), models_count + 1);
+            let index =
+                std::fs::read_to_string("tests_output/typescript/src/model/index.ts").unwrap();
+            assert_eq!(index.matches("export * from").count(), models_count);
+
+            let farm =
+                std::fs::read_to_string("tests_output/typescript/src/model/farm.ts").unwrap();
+            assert!(farm.contains("herd?: Cow[];"));
+
+            // client per resource group + error class
+            let files = std::fs::read_dir("tests_output/typescript/src/client").unwrap();
+            assert_eq!(files.count(), 2);
+        }
+        _ => {}
+    };
+
+    let spec = openapi::from_path("examples/openapi/client.yaml").unwrap();
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            let resource_groups =
+                ram::generate_resources_v3(&spec, &specpath, GroupingStrategy::FirstTag).unwrap();
+            let cfg = Config {
+                vars: vec![("client".to_string(), serde_json::json!(true))]
+                    .into_iter()
+                    .collect(),
+                ..config("typescript")
+            };
+            let state = ram::create_state(cfg, models, resource_groups, false).unwrap();
+            let files = ram::generate_files(state).unwrap();
+            let file = |name: &str| {
+                files
+                    .iter()
+                    .find(|(path, _)| path.ends_with(name))
+                    .map(|(_, data)| data.clone())
+                    .unwrap()
+            };
+
+            // nullable item types are parenthesized, keys which aren't identifiers are quoted
+            let pet = file("model/pet.ts");
+            assert!(pet.contains("tags?: (string | null)[];"));
+            assert!(pet.contains("\"nick-name\"?: string;"));
+
+            // path params are encoded
+            let client = file("client/pet.ts");
+            assert!(client.contains("/pets/${encodeURIComponent(petId)}"));
+            assert!(client.contains("query: { \"page-size\"?: number; }"));
+        }
+        _ => {}
+    };
+}
+
+#[test]
+fn it_generates_python() {
+    for (style, base) in &[("dataclass", "@dataclass"), ("pydantic", "BaseModel")] {
+        let mut vars = HashMap::new();
+        vars.insert(
+            "pydantic".to_string(),
+            serde_json::json!(*style == "pydantic"),
+        );
+        let cfg = Config {
+            vars,
+            ..config("python")
+        };
+        let output = PathBuf::from(format!("tests_output/python_{}", style));
+
+        let spec = openapi::from_path("examples/openapi/allof.yaml").unwrap();
+        let specpath = PathBuf::from("examples/openapi/");
+
+        match spec {
+            openapi::OpenApi::V3_0(spec) => {
+                let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+                let state = ram::create_state(cfg, models, vec![], false).unwrap();
+                ram::util::write_files(&output, ram::generate_files(state).unwrap()).unwrap();
+            }
+            _ => {}
+        };
+
+        let dog = std::fs::read_to_string(output.join("model/dog.py")).unwrap();
+        assert!(dog.contains("class Dog(Animal):"));
+        assert!(dog.contains("owner: Optional[Owner] = None"));
+        let audit = std::fs::read_to_string(output.join("model/audit.py")).unwrap();
+        assert!(audit.contains(base));
+    }
+}
+
+#[test]
+fn it_generates_models_kotlin() {
+    let cfg = config("kotlin");
+    let output = PathBuf::from("tests_output/kotlin");
+
+    let spec = openapi::from_path("examples/openapi/farm.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+
+    // assert vars
+    let models_count = 7;
+    let mut models = vec![];
+
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            assert_eq!(models.len(), models_count);
+            let state = ram::create_state(cfg, models.clone(), vec![], false).unwrap();
+            ram::util::write_files(&output, ram::generate_files(state).unwrap()).unwrap();
+        }
+        _ => {}
+    };
+
+    // map files to name -> file
+    let files: HashMap<String, DirEntry> =
+        std::fs::read_dir(&PathBuf::from("tests_output/kotlin/src/main/kotlin/model"))
+            .unwrap()
+            .map(|f| {
+                let f = f.unwrap();
+                (f.file_name().to_str().unwrap().into(), f)
+            })
+            .collect();
+
+    // assert files count: models
+    assert_eq!(files.len(), models_count);
+
+    // gather some variables from models
+    for model in models {
+        let props_iter = model.properties.iter().chain(
+            model
+                .additional_properties
+                .iter()
+                .flat_map(|p| &p.properties),
+        );
+
+        // val should occur in every constructor property
+        let count_val = props_iter.clone().count();
+        let count_int = props_iter
+            .clone()
+            .filter(|p| {
+                p.schema_type == "integer"
+                    || (p.schema_type == "array"
+                        && match p.items.as_ref() {
+                            Some(item) => item.schema_type == "integer",
+                            _ => false,
+                        })
+            })
+            .count();
+        let count_nullable = props_iter
+            .clone()
+            .filter(|p| p.nullable || !p.required)
+            .count();
+        let count_list = props_iter
+            .clone()
+            .filter(|p| p.schema_type == "array")
+            .count();
+
+        let contents: String = std::fs::read_to_string(
+            files
+                .get(&format!("{}.kt", &model.name.to_pascal_case()))
+                .unwrap()
+                .path(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            Regex::new(r"val ").unwrap().find_iter(&contents).count(),
+            count_val
+        );
+        assert_eq!(
+            Regex::new(r"\bInt\b").unwrap().find_iter(&contents).count(),
+            count_int
+        );
+        assert_eq!(
+            Regex::new(r"List<.+>")
+                .unwrap()
+                .find_iter(&contents)
+                .count(),
+            count_list
+        );
+        assert_eq!(
+            Regex::new(r"\?.*").unwrap().find_iter(&contents).count(),
+            count_nullable
+        );
+    }
+}
+
+#[test]
+fn it_generates_models_csharp() {
+    let cfg = config("csharp");
+    let output = PathBuf::from("tests_output/csharp");
+
+    let spec = openapi::from_path("examples/openapi/farm.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+
+    // assert vars
+    let models_count = 7;
+    let mut models = vec![];
+
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            assert_eq!(models.len(), models_count);
+            let state = ram::create_state(cfg, models.clone(), vec![], false).unwrap();
+            ram::util::write_files(&output, ram::generate_files(state).unwrap()).unwrap();
+        }
+        _ => {}
+    };
+
+    // map files to name -> file
+    let files: HashMap<String, DirEntry> =
+        std::fs::read_dir(&PathBuf::from("tests_output/csharp/Model"))
+            .unwrap()
+            .map(|f| {
+                let f = f.unwrap();
+                (f.file_name().to_str().unwrap().into(), f)
+            })
+            .collect();
+
+    // assert files count: models
+    assert_eq!(files.len(), models_count);
+
+    // gather some variables from models
+    for model in models {
+        let props_iter = model.properties.iter().chain(
+            model
+                .additional_properties
+                .iter()
+                .flat_map(|p| &p.properties),
+        );
+
+        // public should occur in properties and record def
+        let count_public = props_iter.clone().count() + 1;
+        let count_int = props_iter
+            .clone()
+            .filter(|p| {
+                p.schema_type == "integer"
+                    || (p.schema_type == "array"
+                        && match p.items.as_ref() {
+                            Some(item) => item.schema_type == "integer",
+                            _ => false,
+                        })
+            })
+            .count();
+        let count_nullable = props_iter
+            .clone()
+            .filter(|p| p.nullable || !p.required)
+            .count();
+        let count_list = props_iter
+            .clone()
+            .filter(|p| p.schema_type == "array")
+            .count();
+
+        let contents: String = std::fs::read_to_string(
+            files
+                .get(&format!("{}.cs", &model.name.to_pascal_case()))
+                .unwrap()
+                .path(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            Regex::new(r"public ").unwrap().find_iter(&contents).count(),
+            count_public
+        );
+        assert_eq!(
+            Regex::new(r"\bint\b").unwrap().find_iter(&contents).count(),
+            count_int
+        );
+        assert_eq!(
+            Regex::new(r"List<.+>")
+                .unwrap()
+                .find_iter(&contents)
+                .count(),
+            count_list
+        );
+        assert_eq!(
+            Regex::new(r"\?.*").unwrap().find_iter(&contents).count(),
+            count_nullable
+        );
+    }
+}
+
+#[test]
+fn it_generates_polymorphic_models_csharp() {
+    let spec = openapi::from_path("examples/openapi/oneof.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            let state = ram::create_state(config("csharp"), models, vec![], false).unwrap();
+            let files = ram::generate_files(state).unwrap();
+            let file = |name: &str| {
+                files
+                    .iter()
+                    .find(|(path, _)| path.ends_with(name))
+                    .map(|(_, data)| data.clone())
+                    .unwrap()
+            };
+
+            let pet = file("Pet.cs");
+            assert!(pet.contains("[JsonPolymorphic(TypeDiscriminatorPropertyName = \"petType\")]"));
+            assert!(pet.contains("[JsonDerivedType(typeof(Dog), \"dog\")]"));
+            assert!(pet.contains("public interface Pet"));
+
+            // variants implement the polymorphic type, tag is written by the serializer
+            let dog = file("Dog.cs");
+            assert!(dog.contains("public record Dog : Pet"));
+            assert!(!dog.contains("petType"));
+            assert!(dog.contains("Barks"));
+
+            let contact = file("Contact1.cs");
+            assert!(contact.contains("public record Contact1 : Contact"));
+        }
+        _ => {}
+    };
+}
+
+#[test]
+fn it_generates_protobuf() {
+    let output = PathBuf::from("tests_output/protobuf");
+    let spec = openapi::from_path("examples/openapi/farm.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+
+    let generate = || {
+        // lockfile is relative to config path
+        let cfg = Config {
+            lockfile: Some("../tests_output/protobuf/proto.lock".into()),
+            ..config("protobuf")
+        };
+        match &spec {
+            openapi::OpenApi::V3_0(spec) => {
+                let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+                let resource_groups =
+                    ram::generate_resources_v3(&spec, &specpath, GroupingStrategy::FirstTag)
+                        .unwrap();
+                let lock = ram::FieldLock::load_file(&cfg.get_lockfile().unwrap()).unwrap();
+                let state = ram::create_state_with_webhooks(
+                    cfg,
+                    models,
+                    resource_groups,
+                    vec![],
+                    Some(lock),
+                    false,
+                )
+                .unwrap();
+                ram::write_lockfile(&state).unwrap();
+
+                // lockfile is not among generated files
+                let files = ram::generate_files(state).unwrap();
+                assert!(files.keys().all(|path| !path.ends_with("proto.lock")));
+                ram::util::write_files(&output, files).unwrap();
+            }
+            _ => {}
+        };
+        std::fs::read_to_string(output.join("proto/model.proto")).unwrap()
+    };
+
+    // numbering is kept on regeneration with lockfile
+    let first = generate();
+    assert!(output.join("proto.lock").exists());
+    assert_eq!(generate(), first);
+
+    assert!(first.contains("message Farm {"));
+    assert!(Regex::new(r"repeated Cow herd = \d+;")
+        .unwrap()
+        .is_match(&first));
+
+    let service = std::fs::read_to_string(output.join("proto/service.proto")).unwrap();
+    assert!(service.contains("rpc GetFarm(GetFarmRequest) returns (Farm);"));
+}
+
+#[test]
+fn it_generates_protobuf_enums_and_responses() {
+    let specpath = PathBuf::from("examples/openapi/");
+    let proto = |path: &str, name: &str| {
+        let spec = openapi::from_path(path).unwrap();
+        match spec {
+            openapi::OpenApi::V3_0(spec) => {
+                let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+                let resource_groups =
+                    ram::generate_resources_v3(&spec, &specpath, GroupingStrategy::FirstTag)
+                        .unwrap();
+                ram::create_state(config("protobuf"), models, resource_groups, false).map(|state| {
+                    let files = ram::generate_files(state).unwrap();
+                    files
+                        .iter()
+                        .find(|(path, _)| path.ends_with(name))
+                        .map(|(_, data)| data.clone())
+                        .unwrap()
+                })
+            }
+            _ => unreachable!(),
+        }
+    };
+
+    // enum values are numbered from 1, 0 is the default value
+    let model = proto("examples/openapi/enum.yaml", "model.proto").unwrap();
+    assert!(model
+        .contains("  COLOR_UNSPECIFIED = 0;\n  COLOR_LIGHT_RED = 1;\n  COLOR_DARK_BLUE = 2;\n"));
+
+    // repeated fields can't be nested
+    let err = proto("examples/openapi/array.yaml", "model.proto").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "#/components/schemas/Matrix/items: \
+         nested arrays are not supported by lang, wrap items in an object"
+    );
+
+    // responses which aren't messages are wrapped
+    let spec = openapi::from_path("examples/openapi/array.yaml").unwrap();
+    if let openapi::OpenApi::V3_0(spec) = spec {
+        let resource_groups =
+            ram::generate_resources_v3(&spec, &specpath, GroupingStrategy::FirstTag).unwrap();
+        let state = ram::create_state(config("protobuf"), vec![], resource_groups, false).unwrap();
+        let files = ram::generate_files(state).unwrap();
+        let (_, service) = files
+            .iter()
+            .find(|(path, _)| path.ends_with("service.proto"))
+            .unwrap();
+        assert!(service.contains("message CountPetsResponse {\n  int32 body = 1;\n}"));
+        assert!(service.contains("rpc CountPets(CountPetsRequest) returns (CountPetsResponse);"));
+    }
+}
+
+// custom emitter writing the number of models
+struct CountEmitter;
+
+impl ram::Emitter for CountEmitter {
+    fn emit(
+        &self,
+        state: &ram::State,
+        filename: Option<&str>,
+    ) -> ram::Result<Vec<(PathBuf, String)>> {
+        Ok(vec![(
+            PathBuf::from(filename.unwrap_or("count.txt")),
+            state.models.len().to_string(),
+        )])
+    }
+}
+
+#[test]
+fn it_runs_emitters() {
+    let emitter_file = |emitter: &str, filename: Option<&str>| ram::AddFile {
+        filename: filename.map(String::from),
+        template: String::new(),
+        emitter: Some(emitter.into()),
+        file_in: None,
+        path: Some("schemas".into()),
+    };
+    let cfg = Config {
+        files: vec![
+            emitter_file("jsonschema", None),
+            emitter_file("dts", Some("models.d.ts")),
+            emitter_file("count", None),
+        ],
+        ..config("rust")
+    };
+    let output = PathBuf::from("tests_output/emitters");
+
+    let spec = openapi::from_path("examples/openapi/farm.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+    match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            let state = ram::create_state(cfg, models, vec![], false).unwrap();
+
+            // custom emitters are known only when given
+            let emitters: &[(&str, &dyn ram::Emitter)] = &[("count", &CountEmitter)];
+            assert_eq!(ram::validate_files(&state).len(), 1);
+            assert!(ram::validate_files_with_emitters(&state, emitters).is_empty());
+
+            let (files, sources) = ram::generate_files_with_emitters(state, emitters).unwrap();
+            assert_eq!(
+                sources[&PathBuf::from("schemas/count.txt")],
+                "emitter:count"
+            );
+            ram::util::write_files(&output, files).unwrap();
+        }
+        _ => {}
+    };
+
+    let count = std::fs::read_to_string(output.join("schemas/count.txt")).unwrap();
+    assert_eq!(count, "7");
+
+    let schema: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(output.join("schemas/Farm.schema.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert_eq!(schema["properties"]["tails"]["$ref"], "#/$defs/Dog");
+    // referenced models are bundled transitively
+    assert!(schema["$defs"]["Dog"].is_object());
+    assert!(schema["$defs"]["Loop2"].is_object());
+
+    let dts = std::fs::read_to_string(output.join("schemas/models.d.ts")).unwrap();
+    assert!(dts.contains("export interface Farm {"));
+    assert!(dts.contains("herd?: Array<Cow>;"));
+}
+
+#[test]
+fn it_generates_sql_dialects() {
+    let spec = openapi::from_path("examples/openapi/farm.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+
+    let generate = |lang: &str| {
+        let cfg = config(lang);
+        let output = PathBuf::from("tests_output/sql").join(lang);
+        match &spec {
+            openapi::OpenApi::V3_0(spec) => {
+                let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+                let state = ram::create_state(cfg, models, vec![], false).unwrap();
+                ram::util::write_files(&output, ram::generate_files(state).unwrap()).unwrap();
+            }
+            _ => {}
+        };
+        std::fs::read_to_string(output.join("schema.sql")).unwrap()
+    };
+
+    let sqlite = generate("sqlite");
+    assert!(sqlite.contains("CREATE TABLE farm ("));
+    assert!(sqlite.contains("id INTEGER PRIMARY KEY AUTOINCREMENT"));
+    assert!(sqlite.contains("cow_id INTEGER NOT NULL REFERENCES cow(id) ON DELETE CASCADE"));
+
+    let mysql = generate("mysql");
+    assert!(mysql.contains("id BIGINT NOT NULL AUTO_INCREMENT"));
+    assert!(mysql.contains("PRIMARY KEY (id)"));
+    assert!(mysql.contains(") ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;"));
+    assert!(!mysql.contains("SEQUENCE"));
+
+    // primary key is snake cased like its column, enum literals are escaped
+    let spec = openapi::from_path("examples/openapi/enum.yaml").unwrap();
+    let generate = |lang: &str| match &spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            let state = ram::create_state(config(lang), models, vec![], false).unwrap();
+            let files = ram::generate_files(state).unwrap();
+            files
+                .iter()
+                .find(|(path, _)| path.ends_with("schema.sql"))
+                .map(|(_, data)| data.clone())
+                .unwrap()
+        }
+        _ => unreachable!(),
+    };
+
+    let sqlite = generate("sqlite");
+    assert!(sqlite.contains("pen_id INTEGER PRIMARY KEY AUTOINCREMENT"));
+    assert!(sqlite.contains("CHECK (kind IN ('small', 'it''s large'))"));
+
+    let mysql = generate("mysql");
+    assert!(mysql.contains("pen_id BIGINT NOT NULL AUTO_INCREMENT"));
+    assert!(mysql.contains("PRIMARY KEY (pen_id)"));
+    assert!(mysql.contains("kind ENUM('small', 'it''s large') NOT NULL"));
+}
+
+#[test]
+fn it_generates_migrations() {
+    let specpath = PathBuf::from("examples/openapi/");
+    let state = |file: &str| {
+        let cfg = config("postgresql");
+        match openapi::from_path(specpath.join(file)).unwrap() {
+            openapi::OpenApi::V3_0(spec) => {
+                let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+                ram::create_state(cfg, models, vec![], false).unwrap()
+            }
+            _ => panic!("unexpected spec version"),
+        }
+    };
+    let (previous, current) = (state("kennel_v1.yaml"), state("kennel_v2.yaml"));
+
+    let files = ram::generate_migration(&previous, &current, "kennel").unwrap();
+    assert_eq!(files.len(), 2);
+    let file = |suffix: &str| {
+        files
+            .iter()
+            .find(|(path, _)| path.to_string_lossy().ends_with(suffix))
+            .map(|(path, data)| {
+                assert!(path.starts_with("migrations"));
+                data.clone()
+            })
+            .unwrap()
+    };
+
+    let up = file("_kennel.up.sql");
+    assert!(up.contains("CREATE TABLE kennel ("));
+    assert!(up.contains("ALTER TABLE dog ADD COLUMN owner_id bigint;"));
+    assert!(up.contains("ALTER TABLE dog DROP COLUMN age;"));
+    assert!(up.contains("CREATE INDEX idx_dog_name ON dog USING btree (name);"));
+    // foreign keys are added after columns
+    assert!(
+        up.find("ADD CONSTRAINT fk_dog_owner_id").unwrap()
+            > up.find("ADD COLUMN owner_id").unwrap()
+    );
+
+    let down = file("_kennel.down.sql");
+    assert!(down.contains("DROP TABLE kennel;"));
+    assert!(down.contains("ALTER TABLE dog ADD COLUMN age int NOT NULL;"));
+    assert!(
+        down.find("DROP CONSTRAINT fk_dog_owner_id").unwrap()
+            < down.find("DROP COLUMN owner_id").unwrap()
+    );
+
+    // unchanged tables produce no migration
+    assert!(ram::generate_migration(&current, &current, "kennel")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn it_checks_generated_files() {
+    let cfg = config("rust");
+    let output = PathBuf::from("tests_output/check");
+    let _ = std::fs::remove_dir_all(&output);
+
+    let spec = openapi::from_path("examples/openapi/farm.yaml").unwrap();
+    let specpath = PathBuf::from("examples/openapi/");
+    let files = match spec {
+        openapi::OpenApi::V3_0(spec) => {
+            let models = ram::generate_models_v3(&spec, &specpath).unwrap();
+            let state = ram::create_state(cfg, models, vec![], false).unwrap();
+            ram::generate_files(state).unwrap()
+        }
+        _ => panic!("unexpected spec version"),
+    };
+    ram::util::write_files(&output, files.clone()).unwrap();
+    assert!(ram::check_files(&output, &files, &ram::Ignore::default())
+        .unwrap()
+        .is_empty());
+
+    let model_dir = output.join("src/model");
+    std::fs::write(model_dir.join("stale.rs"), "").unwrap();
+    std::fs::remove_file(model_dir.join("cow.rs")).unwrap();
+    let farm = std::fs::read_to_string(model_dir.join("farm.rs")).unwrap();
+    std::fs::write(
+        model_dir.join("farm.rs"),
+        farm.replace("pub id", "pub ident"),
+    )
+    .unwrap();
+
+    let checks = ram::check_files(&output, &files, &ram::Ignore::default()).unwrap();
+    assert_eq!(checks.len(), 3);
+    assert!(checks.contains(&ram::FileCheck::Missing(model_dir.join("cow.rs"))));
+    assert!(checks.contains(&ram::FileCheck::Extra(model_dir.join("stale.rs"))));
+    assert!(checks.iter().any(|c| match c {
+        ram::FileCheck::Changed(path, diff) =>
+            path == &model_dir.join("farm.rs") && diff.contains("\n-    pub ident"),
+        _ => false,
+    }));
+}
+
+#[test]
+fn it_keeps_protected_regions_and_removes_stale_files() {
+    let output = PathBuf::from("tests_output/regions");
+    let _ = std::fs::remove_dir_all(&output);
+
+    let rendered = "struct Dog;\n// ram:keep-begin impl\n// ram:keep-end\n";
+    let files: HashMap<PathBuf, String> = vec![
+        (PathBuf::from("dog.rs"), rendered.to_string()),
+        (PathBuf::from("cat.rs"), "struct Cat;\n".to_string()),
+    ]
+    .into_iter()
+    .collect();
+    let sources: HashMap<PathBuf, String> = files
+        .keys()
+        .map(|f| (f.clone(), "model.hbs".to_string()))
+        .collect();
+    ram::util::write_files(&output, files.clone()).unwrap();
+    let manifest = ram::Manifest::new(&output, &sources);
+    assert_eq!(manifest.files.len(), 2);
+
+    // handwritten code in region survives regeneration
+    let edited = rendered.replace("impl\n", "impl\nimpl Dog {}\n");
+    std::fs::write(output.join("dog.rs"), &edited).unwrap();
+    let plan = ram::util::plan_files(&output, &files, &ram::Ignore::default()).unwrap();
+    assert!(plan
+        .iter()
+        .all(|(_, change, _)| *change == ram::util::FileChange::Unchanged));
+    ram::util::write_files(&output, files.clone()).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(output.join("dog.rs")).unwrap(),
+        edited
+    );
+
+    // cat is not generated anymore
+    let mut sources = sources;
+    sources.remove(&PathBuf::from("cat.rs"));
+    let removed = ram::Manifest::new(&output, &sources)
+        .remove_stale(&manifest, &output, &ram::Ignore::default())
+        .unwrap();
+    assert_eq!(removed, vec![output.join("cat.rs")]);
+    assert!(output.join("dog.rs").exists());
+}
+
+#[test]
+fn it_ignores_files_relative_to_output() {
+    let output = PathBuf::from("tests_output/ignore");
+    let _ = std::fs::remove_dir_all(&output);
+    std::fs::create_dir_all(&output).unwrap();
+    std::fs::write(output.join(".ramignore"), "*.rs\n!src/keep.rs\n").unwrap();
+
+    let files: HashMap<PathBuf, String> = vec!["src/pet.rs", "src/keep.rs", "src/pet.go"]
+        .into_iter()
+        .map(|f| (PathBuf::from(f), String::new()))
+        .collect();
+    ram::util::write_files(&output, files).unwrap();
+
+    assert!(!output.join("src/pet.rs").exists());
+    assert!(output.join("src/keep.rs").exists());
+    assert!(output.join("src/pet.go").exists());
+}
+
+#[test]
+fn it_collects_watched_files() {
+    let dir = PathBuf::from("tests_output/watch");
+    std::fs::create_dir_all(dir.join("templates")).unwrap();
+    std::fs::write(dir.join("templates/extra.hbs"), "").unwrap();
+    std::fs::write(
+        dir.join("config.yaml"),
+        "lang: rust\nfiles:\n  - template: templates/extra.hbs\n    filename: extra.rs\n",
+    )
+    .unwrap();
+
+    let cfg = Config::load_file(&dir.join("config.yaml")).unwrap();
+    let files =
+        ram::watch::watched_files(&cfg, &[PathBuf::from("examples/openapi/farm.yaml")]).unwrap();
+    let names: Vec<String> = files
+        .iter()
+        .map(|f| f.file_name().unwrap().to_string_lossy().into())
+        .collect();
+    for name in &[
+        "farm.yaml",
+        "animals.yaml",
+        "loop.yaml",
+        "loop2.yaml",
+        "extra.hbs",
+    ] {
+        assert!(names.contains(&name.to_string()), "{} is not watched", name);
+    }
+
+    // all watched files exist
+    let snapshot = ram::watch::snapshot(&files);
+    assert_eq!(snapshot.len(), files.len());
+    assert!(snapshot.iter().all(|(_, modified)| modified.is_some()));
+}
+
+#[test]
+fn it_validates_files() {
+    let dir = PathBuf::from("tests_output/validate");
+    std::fs::create_dir_all(dir.join("templates")).unwrap();
+    std::fs::write(dir.join("templates/broken.hbs"), "{{#if x}}").unwrap();
+    std::fs::write(
+        dir.join("config.yaml"),
+        "lang: rust\n\
+         files:\n  \
+           - template: templates/broken.hbs\n    filename: broken.rs\n  \
+           - template: templates/missing.hbs\n    filename: missing.rs\n  \
+           - emitter: unknown\n",
+    )
+    .unwrap();
+
+    let cfg = Config::load_file(&dir.join("config.yaml")).unwrap();
+    let state = ram::create_state(cfg, vec![], vec![], false).unwrap();
+    assert_eq!(ram::validate_files(&state).len(), 3);
+
+    let cfg = config("rust");
+    let state = ram::create_state(cfg, vec![], vec![], false).unwrap();
+    assert!(ram::validate_files(&state).is_empty());
+
+    let langs = ram::Assets::langs();
+    assert!(langs.contains(&"rust".to_string()));
+    assert!(langs.contains(&"postgresql".to_string()));
+}
+
+#[test]
+fn it_ejects_built_in_lang() {
+    let dir = PathBuf::from("tests_output/eject");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let spec = ram::Assets::eject("rust", &dir.join("rust"), false).unwrap();
+    assert_eq!(spec, dir.join("rust/rust.yaml"));
+    assert!(dir.join("rust/model.hbs").exists());
+    assert!(dir.join("rust/mod_model.hbs").exists());
+    assert!(ram::Assets::eject("cobol", &dir, false).is_err());
+
+    // existing files are kept unless forced
+    assert!(ram::Assets::eject("rust", &dir.join("rust"), false).is_err());
+    assert!(ram::Assets::eject("rust", &dir.join("rust"), true).is_ok());
+
+    let config = ram::set_config_lang("lang: rust\n", "rust/rust.yaml");
+    std::fs::write(dir.join("config.yaml"), config).unwrap();
+    let cfg = Config::load_file(&dir.join("config.yaml")).unwrap();
+    let lang = cfg.get_lang().unwrap();
+    let ejected = lang.ejected_from.as_ref().unwrap();
+    assert_eq!(ejected.lang, "rust");
+    assert_eq!(ejected.version, ram::VERSION);
+    assert!(ejected.hashes.contains_key("model.hbs"));
+    assert!(ram::Assets::changed_since_eject(ejected).is_empty());
+
+    // changed built-in files are reported
+    let mut outdated = ejected.clone();
+    outdated.hashes.insert("model.hbs".into(), "0".into());
+    outdated.hashes.insert("removed.hbs".into(), "0".into());
+    assert_eq!(
+        ram::Assets::changed_since_eject(&outdated),
+        vec!["model.hbs", "removed.hbs"]
+    );
+    assert!(lang
+        .files_relative()
+        .iter()
+        .all(|f| PathBuf::from(&f.template).exists()));
+}
+
+#[test]
+fn it_merges_specs() {
+    let load = |path: &str| match openapi::from_path(path).unwrap() {
+        openapi::OpenApi::V3_0(spec) => {
+            let specpath = PathBuf::from("examples/openapi/");
+            ram::SpecModels {
+                title: spec.info.title.clone(),
+                models: ram::generate_models_v3(&spec, &specpath).unwrap(),
+                resource_groups: ram::generate_resources_v3(
+                    &spec,
+                    &specpath,
+                    GroupingStrategy::FirstTag,
+                )
+                .unwrap(),
+                webhook_groups: vec![],
+            }
+        }
+        _ => panic!("unexpected spec version"),
+    };
+
+    // same spec twice is merged into one
+    let farm = load("examples/openapi/farm.yaml");
+    let (models, resource_groups, _) =
+        ram::merge_specs(vec![farm.clone(), farm.clone()], ram::ConflictPolicy::Error).unwrap();
+    assert_eq!(models.len(), farm.models.len());
+    assert_eq!(resource_groups.len(), farm.resource_groups.len());
+    assert_eq!(
+        resource_groups[0].resources.len(),
+        farm.resource_groups[0].resources.len()
+    );
+
+    // same operation differing between specs is a conflict, regardless of policy
+    let mut other = farm.clone();
+    other.title = "Other farm".into();
+    let resource = &mut other.resource_groups[0].resources[0];
+    resource.name = "fetchFarm".into();
+    let operation = format!("{} {}", resource.method, resource.path);
+    let err = ram::merge_specs(vec![farm.clone(), other], ram::ConflictPolicy::Prefix).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&format!("{} ({}, Other farm)", operation, farm.title)));
+
+    let specs = vec![
+        load("examples/openapi/kennel_v1.yaml"),
+        load("examples/openapi/kennel_v2.yaml"),
+    ];
+    let err = ram::merge_specs(specs.clone(), ram::ConflictPolicy::Error).unwrap_err();
+    assert!(err.to_string().contains("Dog (Kennel, before migration"));
+
+    let (models, resource_groups, _) =
+        ram::merge_specs(specs, ram::ConflictPolicy::Prefix).unwrap();
+    let defs: Vec<&str> = models.iter().map(|m| m.def.as_str()).collect();
+    assert!(defs.contains(&"KennelBeforeMigrationDog"));
+    assert!(defs.contains(&"KennelAfterMigrationDog"));
+    assert!(defs.contains(&"Kennel"));
+
+    let cfg = config("rust");
+    let state = ram::create_state(cfg, models, resource_groups, false).unwrap();
+    assert!(state
+        .models
+        .iter()
+        .any(|m| m.name == "KennelAfterMigrationDog"));
+}